@@ -0,0 +1,198 @@
+//! Async reading of leases with tokio.
+//!
+//! [`LeaseStream`] and [`parse_async`](crate::parser::parse_async) read from
+//! any caller-supplied [`AsyncRead`], such as a socket or a pipe, and never
+//! block the runtime. [`AsyncLeaseFollower`] opens the leases file itself
+//! through `tokio::fs`, which, like every file access in tokio, runs the
+//! reads and the `metadata` calls on the blocking thread pool, so each poll
+//! occupies a blocking thread for as long as the file takes to read.
+
+use std::collections::VecDeque;
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures_core::Stream;
+use tokio::fs::{self, File};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeekExt, ReadBuf};
+
+use crate::follow::{file_id, FollowBatch, FollowState};
+use crate::leases::Lease;
+use crate::leases::Leases;
+use crate::parser;
+use crate::parser::ParseError;
+use crate::reader::BlockBuffer;
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Stream of leases parsed incrementally from an async reader, the async
+/// counterpart of [`LeaseReader`](crate::reader::LeaseReader)
+pub struct LeaseStream<R> {
+    reader: R,
+    chunk: Box<[u8]>,
+    buffer: BlockBuffer,
+    parsed: VecDeque<Lease>,
+    done: bool,
+}
+
+impl<R: AsyncRead + Unpin> LeaseStream<R> {
+    pub fn new(reader: R) -> LeaseStream<R> {
+        LeaseStream {
+            reader,
+            chunk: vec![0; CHUNK_SIZE].into_boxed_slice(),
+            buffer: BlockBuffer::default(),
+            parsed: VecDeque::new(),
+            done: false,
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    fn poll_fill(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), ParseError>> {
+        while self.parsed.is_empty() && !self.done {
+            let mut buf = ReadBuf::new(&mut self.chunk);
+            match Pin::new(&mut self.reader).poll_read(cx, &mut buf) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e.to_string())),
+                Poll::Ready(Ok(())) => (),
+            }
+
+            let n = buf.filled().len();
+            if n == 0 {
+                self.done = true;
+                if !self.buffer.pending().trim().is_empty() {
+                    return Poll::Ready(Err(
+                        "Incomplete declaration at the end of input".to_owned()
+                    ));
+                }
+                break;
+            }
+
+            self.buffer.push(&self.chunk[..n])?;
+            if let Some(text) = self.buffer.take_complete() {
+                self.parsed
+                    .extend(parser::parse(text)?.leases.iter().cloned());
+            }
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<R: AsyncRead + Unpin> Stream for LeaseStream<R> {
+    type Item = Result<Lease, ParseError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        if let Poll::Ready(Err(e)) = this.poll_fill(cx) {
+            this.done = true;
+            this.parsed.clear();
+            return Poll::Ready(Some(Err(e)));
+        }
+
+        match this.parsed.pop_front() {
+            Some(l) => Poll::Ready(Some(Ok(l))),
+            None if this.done => Poll::Ready(None),
+            None => Poll::Pending,
+        }
+    }
+}
+
+/// Async counterpart of [`LeaseFollower`](crate::follow::LeaseFollower).
+///
+/// The file is accessed through `tokio::fs`, see the [module](self)
+/// documentation. To keep the file off the blocking pool, read it with a
+/// [`LeaseStream`] over a caller-supplied reader instead.
+#[derive(Debug)]
+pub struct AsyncLeaseFollower {
+    path: PathBuf,
+    file: File,
+    state: FollowState,
+}
+
+impl AsyncLeaseFollower {
+    pub async fn open<P: AsRef<Path>>(path: P) -> Result<AsyncLeaseFollower, String> {
+        let path = path.as_ref().to_path_buf();
+        let (file, file_id) = open_file(&path).await?;
+
+        let mut follower = AsyncLeaseFollower {
+            path,
+            file,
+            state: FollowState::new(file_id),
+        };
+        follower.read_appended().await?;
+
+        Ok(follower)
+    }
+
+    /// Returns all leases read so far
+    pub fn leases(&self) -> &Leases {
+        self.state.leases()
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Reads leases appended since the last call
+    pub async fn poll(&mut self) -> Result<FollowBatch, String> {
+        // a missing file means dhcpd is in the middle of replacing it
+        let resynced = match fs::metadata(&self.path).await {
+            Ok(meta) => self.state.needs_resync(&meta),
+            Err(_) => false,
+        };
+        if resynced {
+            let (file, file_id) = open_file(&self.path).await?;
+            self.file = file;
+            self.state.reset(file_id);
+        }
+
+        let leases = self.read_appended().await?;
+
+        Ok(FollowBatch { leases, resynced })
+    }
+
+    /// Waits until some leases are appended, checking the file every
+    /// `interval`
+    pub async fn wait(&mut self, interval: Duration) -> Result<FollowBatch, String> {
+        loop {
+            let batch = self.poll().await?;
+            if batch.resynced || !batch.leases.is_empty() {
+                return Ok(batch);
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+    async fn read_appended(&mut self) -> Result<Vec<Lease>, String> {
+        self.file
+            .seek(SeekFrom::Start(self.state.offset()))
+            .await
+            .map_err(|e| format!("{}: {}", self.path.display(), e))?;
+
+        let mut bytes = Vec::new();
+        self.file
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(|e| format!("{}: {}", self.path.display(), e))?;
+
+        self.state.feed(&bytes)
+    }
+}
+
+async fn open_file(path: &Path) -> Result<(File, Option<u64>), String> {
+    let file = File::open(path)
+        .await
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+    let meta = file
+        .metadata()
+        .await
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    Ok((file, file_id(&meta)))
+}
@@ -0,0 +1,639 @@
+//! Command-line tool for querying `dhcpd.leases` files, run with `--help` for
+//! usage.
+
+use std::env;
+use std::io::{self, Read, Write};
+use std::process;
+use std::time::Duration;
+
+use dhcpd_parser::common::Date;
+use dhcpd_parser::decompress;
+use dhcpd_parser::export::{self, Column, ExportOptions};
+use dhcpd_parser::history;
+use dhcpd_parser::leases::{Lease, LeaseState, Leases, LeasesMethods};
+use dhcpd_parser::net::Prefix;
+use dhcpd_parser::oui::OuiDb;
+use dhcpd_parser::parser::{self, ParserResult};
+use dhcpd_parser::query::{self, LeaseFilter};
+use dhcpd_parser::reader;
+use dhcpd_parser::stats::{self as statistics, StatsOptions};
+use dhcpd_parser::validate::{self as lint, Severity};
+
+const USAGE: &str = "\
+Usage: dhcpd-leases <command> [options] [file...]
+
+Reads the given leases files, compressed ones included, or the standard input
+when no file or `-` is given. Several files are merged into one history.
+
+Commands:
+    list        List leases
+    show        Show the full history of an IP or MAC address
+    export      Export leases as JSON, NDJSON or CSV
+    stats       Show statistics of the leases
+    validate    Check every file for problems, fails on errors
+
+Options of list:
+    --active-at <date>  Only leases active at the date, RFC3339 or `now`
+    --mac <mac>         Only leases of the hardware address
+    --ip <ip>           Only leases of the IP address
+    --hostname <name>   Only leases with the hostname or client hostname
+    --subnet <cidr>     Only leases in the network, e.g. 10.0.0.0/24
+    --where <query>     Only leases matching the query, e.g.
+                        'mac ~ \"^00:1a\" and not abandoned'
+    --current           Only the last declaration of every address
+
+Options of show:
+    <ip-or-mac>         Address to show, given before the files
+
+Options of export:
+    --format <fmt>      json (default), ndjson or csv
+    --columns <list>    Comma separated columns, all by default
+    --where <query>     Only leases matching the query
+    --current           Only the last declaration of every address
+
+Options of stats:
+    --resolution <dur>  Interval of the active leases timeline, e.g. 30m, 1h
+                        (default) or 1d
+    --top <n>           Length of the top hostnames, OUIs and vendors, 10 by
+                        default
+    --oui <file>        IEEE registry, oui.txt or oui.csv, to show the top
+                        vendors
+    --where <query>     Only leases matching the query
+
+Options of validate:
+    --strict            Fail on warnings too
+
+Output of list and show:
+    --output <out>      table (default), json or template
+    --template <tmpl>   Template of every line, e.g. '{ip} {mac}', implies
+                        `--output template`
+
+Columns are ip, mac, uid, hostname, client-hostname, starts, ends, abandoned
+and state.
+";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.is_empty() || args.iter().any(|a| a == "-h" || a == "--help") {
+        print!("{}", USAGE);
+        return;
+    }
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    match run(&args, &mut out) {
+        Ok(code) => process::exit(code),
+        Err(Error::Usage(e)) => {
+            eprintln!("dhcpd-leases: {}\n\n{}", e, USAGE);
+            process::exit(2);
+        }
+        Err(Error::Failed(e)) => {
+            eprintln!("dhcpd-leases: {}", e);
+            process::exit(1);
+        }
+    }
+}
+
+enum Error {
+    Usage(String),
+    Failed(String),
+}
+
+impl From<String> for Error {
+    fn from(e: String) -> Error {
+        Error::Failed(e)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Output {
+    Table,
+    Json,
+    Template(String),
+}
+
+#[derive(Default)]
+struct Options {
+    files: Vec<String>,
+    positional: Vec<String>,
+    active_at: Option<Date>,
+    filter: LeaseFilter,
+    current: bool,
+    strict: bool,
+    format: Option<String>,
+    columns: Option<Vec<Column>>,
+    output: Option<Output>,
+    resolution: Option<Duration>,
+    top: Option<usize>,
+    oui: Option<String>,
+}
+
+fn parse_options(args: &[String]) -> Result<Options, Error> {
+    let mut opts = Options::default();
+    let mut template = None;
+    let mut output = None;
+    let mut filters = Vec::new();
+
+    let mut it = args.iter();
+    while let Some(arg) = it.next() {
+        let mut value = || {
+            it.next()
+                .cloned()
+                .ok_or_else(|| Error::Usage(format!("{} requires a value", arg)))
+        };
+
+        match arg.as_str() {
+            "--active-at" => {
+                let date = query::parse_date(&value()?).map_err(Error::Usage)?;
+                opts.active_at = Some(date);
+                filters.push(LeaseFilter::active_at(date));
+            }
+            "--mac" => {
+                filters.push(LeaseFilter::equals(Column::Mac, value()?).map_err(Error::Usage)?)
+            }
+            "--ip" => {
+                filters.push(LeaseFilter::equals(Column::Ip, value()?).map_err(Error::Usage)?)
+            }
+            "--hostname" => {
+                let hn = value()?;
+                let hostname =
+                    LeaseFilter::equals(Column::Hostname, hn.clone()).map_err(Error::Usage)?;
+                let client =
+                    LeaseFilter::equals(Column::ClientHostname, hn).map_err(Error::Usage)?;
+                filters.push(hostname.or(client));
+            }
+            "--subnet" => {
+                let net = Prefix::from(&value()?).map_err(Error::Usage)?;
+                filters.push(LeaseFilter::ip_in(net));
+            }
+            "--where" => filters.push(LeaseFilter::from(&value()?).map_err(Error::Usage)?),
+            "--current" => opts.current = true,
+            "--strict" => opts.strict = true,
+            "--format" => opts.format = Some(value()?),
+            "--columns" => {
+                let columns = value()?
+                    .split(',')
+                    .map(|c| Column::from(c.trim()))
+                    .collect::<Result<Vec<Column>, String>>()
+                    .map_err(Error::Usage)?;
+                opts.columns = Some(columns);
+            }
+            "--resolution" => {
+                opts.resolution = Some(parse_duration(&value()?).map_err(Error::Usage)?)
+            }
+            "--top" => {
+                let top = value()?;
+                let top = top
+                    .parse()
+                    .map_err(|_| Error::Usage(format!("invalid number {}", top)))?;
+                opts.top = Some(top);
+            }
+            "--oui" => opts.oui = Some(value()?),
+            "--output" => output = Some(value()?),
+            "--template" => template = Some(value()?),
+            "-" => opts.files.push(arg.clone()),
+            a if a.starts_with("--") => return Err(Error::Usage(format!("unknown option {}", a))),
+            _ => opts.positional.push(arg.clone()),
+        }
+    }
+
+    opts.filter = filters
+        .into_iter()
+        .fold(LeaseFilter::any(), |acc, f| acc.and(f));
+
+    opts.output = match (output.as_deref(), template) {
+        (None, None) | (Some("table"), None) => Some(Output::Table),
+        (Some("json"), None) => Some(Output::Json),
+        (None, Some(t)) | (Some("template"), Some(t)) => {
+            check_template(&t).map_err(Error::Usage)?;
+            Some(Output::Template(t))
+        }
+        (Some("template"), None) => {
+            return Err(Error::Usage(
+                "--output template requires --template".to_owned(),
+            ))
+        }
+        (Some(o), _) => return Err(Error::Usage(format!("unknown output {}", o))),
+    };
+
+    Ok(opts)
+}
+
+fn run<W: Write>(args: &[String], out: &mut W) -> Result<i32, Error> {
+    let command = args[0].as_str();
+    let mut opts = parse_options(&args[1..])?;
+
+    let target = if command == "show" {
+        if opts.positional.is_empty() {
+            return Err(Error::Usage(
+                "show requires an IP or MAC address".to_owned(),
+            ));
+        }
+        Some(opts.positional.remove(0))
+    } else {
+        None
+    };
+    opts.files.append(&mut opts.positional);
+
+    match command {
+        "list" => list(&read_leases(&opts.files)?, &opts, out)?,
+        "show" => show(
+            &read_leases(&opts.files)?,
+            &target.unwrap_or_default(),
+            &opts,
+            out,
+        )?,
+        "export" => export(&read_leases(&opts.files)?, &opts, out)?,
+        "stats" => stats(&read_leases(&opts.files)?, &opts, out)?,
+        "validate" => return validate(&opts.files, opts.strict, out),
+        c => return Err(Error::Usage(format!("unknown command {}", c))),
+    }
+
+    Ok(0)
+}
+
+/// Reads leases of all `files`, the standard input if there are none
+fn read_leases(files: &[String]) -> Result<Leases, String> {
+    match files {
+        [] => read_stdin(),
+        [f] if f == "-" => read_stdin(),
+        [f] => Ok(parser::parse_path(f)?.leases),
+        _ => {
+            if files.iter().any(|f| f == "-") {
+                return Err("standard input can't be merged with other files".to_owned());
+            }
+            Ok(history::load_files(files)?.into_leases())
+        }
+    }
+}
+
+fn read_stdin() -> Result<Leases, String> {
+    Ok(parser::parse(read_text("-")?)
+        .map_err(|e| format!("<stdin>: {}", e))?
+        .leases)
+}
+
+/// Reads a file, or the standard input for `-`, decompressing it if needed
+fn read_text(f: &str) -> Result<String, String> {
+    let (name, mut reader) = if f == "-" {
+        ("<stdin>", decompress::decompress(io::stdin().lock())?)
+    } else {
+        (f, decompress::open(f)?)
+    };
+
+    let mut input = String::new();
+    reader
+        .read_to_string(&mut input)
+        .map_err(|e| format!("{}: {}", name, e))?;
+
+    Ok(input)
+}
+
+fn filter(leases: &Leases, opts: &Options) -> Leases {
+    let leases = if opts.current {
+        leases.current().into_leases()
+    } else {
+        leases.clone()
+    };
+
+    opts.filter.filter(&leases)
+}
+
+fn list<W: Write>(leases: &Leases, opts: &Options, out: &mut W) -> Result<(), String> {
+    print_leases(&filter(leases, opts), opts, out)
+}
+
+fn show<W: Write>(
+    leases: &Leases,
+    target: &str,
+    opts: &Options,
+    out: &mut W,
+) -> Result<(), String> {
+    let target = target.to_lowercase();
+    let mut res = Leases::new();
+    for l in leases.iter() {
+        let mac = l.hardware.as_ref().map(|hw| hw.mac.to_lowercase());
+        if l.ip == target || mac.as_ref() == Some(&target) {
+            res.push(l.clone());
+        }
+    }
+    if res.is_empty() {
+        return Err(format!("no leases of {}", target));
+    }
+
+    print_leases(&res, opts, out)
+}
+
+const TABLE_COLUMNS: [Column; 6] = [
+    Column::Ip,
+    Column::Mac,
+    Column::Hostname,
+    Column::Starts,
+    Column::Ends,
+    Column::State,
+];
+
+fn print_leases<W: Write>(leases: &Leases, opts: &Options, out: &mut W) -> Result<(), String> {
+    // states are shown at the date the leases were filtered by
+    let now = opts.active_at.unwrap_or_else(Date::now);
+    let columns = opts
+        .columns
+        .clone()
+        .unwrap_or_else(|| TABLE_COLUMNS.to_vec());
+
+    match opts.output.as_ref().unwrap_or(&Output::Table) {
+        Output::Table => {
+            let mut rows = vec![columns.iter().map(|c| c.name().to_uppercase()).collect()];
+            for l in leases.iter() {
+                rows.push(columns.iter().map(|c| cell(*c, l, now)).collect());
+            }
+            write_table(&rows, out)
+        }
+        Output::Json => {
+            let options = ExportOptions {
+                columns,
+                current_only: false,
+                now,
+            };
+            export::write_json(out, leases, &options)
+        }
+        Output::Template(t) => {
+            for l in leases.iter() {
+                writeln!(out, "{}", render(t, l, now)).map_err(|e| e.to_string())?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Table cell of the column, hostname falls back to the client hostname
+fn cell(c: Column, l: &Lease, now: Date) -> String {
+    let value = match c {
+        Column::Hostname => c
+            .value(l, now)
+            .or_else(|| Column::ClientHostname.value(l, now)),
+        _ => c.value(l, now),
+    };
+
+    value.unwrap_or_else(|| "-".to_owned())
+}
+
+fn write_table<W: Write>(rows: &[Vec<String>], out: &mut W) -> Result<(), String> {
+    let mut widths = vec![0; rows[0].len()];
+    for row in rows {
+        for (w, cell) in widths.iter_mut().zip(row) {
+            *w = (*w).max(cell.chars().count());
+        }
+    }
+
+    for row in rows {
+        let line: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, w)| format!("{:<w$}", cell, w = w))
+            .collect();
+        writeln!(out, "{}", line.join("  ").trim_end()).map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
+/// Checks that every `{column}` placeholder of the template is known
+fn check_template(t: &str) -> Result<(), String> {
+    let mut rest = t;
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..]
+            .find('}')
+            .ok_or(format!("unclosed placeholder in template '{}'", t))?;
+        Column::from(&rest[start + 1..start + end])?;
+        rest = &rest[start + end + 1..];
+    }
+
+    Ok(())
+}
+
+/// Replaces the placeholders in one scan, so values are never expanded again
+fn render(t: &str, l: &Lease, now: Date) -> String {
+    let mut res = String::with_capacity(t.len());
+    let mut rest = t;
+    while let Some(start) = rest.find('{') {
+        res.push_str(&rest[..start]);
+        let column = rest[start..]
+            .find('}')
+            .and_then(|end| Some((Column::from(&rest[start + 1..start + end]).ok()?, end)));
+        match column {
+            Some((c, end)) => {
+                res.push_str(&c.value(l, now).unwrap_or_default());
+                rest = &rest[start + end + 1..];
+            }
+            None => {
+                res.push('{');
+                rest = &rest[start + 1..];
+            }
+        }
+    }
+    res.push_str(rest);
+
+    res
+}
+
+fn export<W: Write>(leases: &Leases, opts: &Options, out: &mut W) -> Result<(), String> {
+    let leases = &filter(leases, opts);
+    let options = ExportOptions {
+        columns: opts.columns.clone().unwrap_or_else(|| Column::ALL.to_vec()),
+        current_only: false,
+        now: Date::now(),
+    };
+
+    match opts.format.as_deref().unwrap_or("json") {
+        "json" => export::write_json(out, leases, &options),
+        "ndjson" => export::write_ndjson(out, leases, &options),
+        "csv" => export::write_csv(out, leases, &options),
+        f => Err(format!("unknown export format {}", f)),
+    }
+}
+
+fn stats<W: Write>(leases: &Leases, opts: &Options, out: &mut W) -> Result<(), String> {
+    let leases = &filter(leases, opts);
+    let defaults = StatsOptions::default();
+    let now = defaults.now;
+    let oui = match &opts.oui {
+        Some(path) => Some(OuiDb::load(path)?),
+        #[cfg(feature = "oui-snapshot")]
+        None => Some(OuiDb::snapshot()),
+        #[cfg(not(feature = "oui-snapshot"))]
+        None => None,
+    };
+    let stats = statistics::compute(
+        leases,
+        &StatsOptions {
+            resolution: opts.resolution.unwrap_or(defaults.resolution),
+            top: opts.top.unwrap_or(defaults.top),
+            now,
+            oui,
+        },
+    )?;
+
+    let current = leases.current();
+    let count = |state: LeaseState| current.iter().filter(|l| l.state_at(now) == state).count();
+    let rows = vec![
+        vec!["declarations".to_owned(), stats.declarations.to_string()],
+        vec!["addresses".to_owned(), current.len().to_string()],
+        vec!["clients".to_owned(), stats.clients.to_string()],
+        vec!["active".to_owned(), count(LeaseState::Active).to_string()],
+        vec!["expired".to_owned(), count(LeaseState::Expired).to_string()],
+        vec!["abandoned".to_owned(), stats.abandoned.to_string()],
+        vec!["future".to_owned(), count(LeaseState::Future).to_string()],
+        vec![
+            "renewals".to_owned(),
+            format!("{} ({:.1}%)", stats.renewals, stats.renewal_rate * 100.0),
+        ],
+        vec![
+            "peak".to_owned(),
+            stats.peak.map_or("-".to_owned(), |p| {
+                format!("{} at {}", p.active, p.at.to_rfc3339())
+            }),
+        ],
+    ];
+    write_table(&rows, out)?;
+
+    let mut sections: Vec<(&str, Vec<Vec<String>>)> = Vec::new();
+    sections.push((
+        "Active leases",
+        stats
+            .timeline
+            .iter()
+            .map(|s| vec![s.at.to_rfc3339(), s.active.to_string()])
+            .collect(),
+    ));
+    sections.push((
+        "Lease durations",
+        stats
+            .durations
+            .iter()
+            .map(|b| {
+                let range = match b.max {
+                    Some(max) => format!("{}-{}", format_duration(b.min), format_duration(max)),
+                    None => format!("{}+", format_duration(b.min)),
+                };
+                vec![range, b.count.to_string()]
+            })
+            .collect(),
+    ));
+    sections.push((
+        "Clients per day",
+        stats
+            .days
+            .iter()
+            .map(|d| {
+                vec![
+                    d.day.to_rfc3339()[..10].to_owned(),
+                    format!("new {}", d.new),
+                    format!("returning {}", d.returning),
+                ]
+            })
+            .collect(),
+    ));
+    for (title, top) in [
+        ("Top hostnames", &stats.top_hostnames),
+        ("Top OUIs", &stats.top_ouis),
+        ("Top vendors", &stats.top_vendors),
+    ]
+    .iter()
+    {
+        sections.push((
+            title,
+            top.iter()
+                .map(|(v, n)| vec![v.clone(), n.to_string()])
+                .collect(),
+        ));
+    }
+
+    for (title, rows) in sections.iter().filter(|(_, rows)| !rows.is_empty()) {
+        writeln!(out, "\n{}", title).map_err(|e| e.to_string())?;
+        write_table(rows, out)?;
+    }
+
+    Ok(())
+}
+
+/// Parses durations like `90s`, `30m`, `1h` or `1d`
+fn parse_duration(s: &str) -> Result<Duration, String> {
+    let invalid = || format!("invalid duration {}", s);
+    let unit = match s.chars().last() {
+        Some('s') => 1,
+        Some('m') => 60,
+        Some('h') => 3600,
+        Some('d') => 86400,
+        _ => return Err(invalid()),
+    };
+    let n: u64 = s[..s.len() - 1].parse().map_err(|_| invalid())?;
+    if n == 0 {
+        return Err(invalid());
+    }
+
+    n.checked_mul(unit)
+        .map(Duration::from_secs)
+        .ok_or_else(|| format!("duration {} is too long", s))
+}
+
+fn format_duration(d: Duration) -> String {
+    let secs = d.as_secs();
+    for &(unit, suffix) in [(86400, "d"), (3600, "h"), (60, "m")].iter() {
+        if secs >= unit && secs.is_multiple_of(unit) {
+            return format!("{}{}", secs / unit, suffix);
+        }
+    }
+
+    format!("{}s", secs)
+}
+
+/// Validates every file on its own, reporting findings with their locations
+fn validate<W: Write>(files: &[String], strict: bool, out: &mut W) -> Result<i32, Error> {
+    let files = if files.is_empty() {
+        vec!["-".to_owned()]
+    } else {
+        files.to_vec()
+    };
+
+    let (mut errors, mut warnings) = (0, 0);
+    for f in files.iter() {
+        // parsed as written, so that findings point at the declarations
+        let name = if f == "-" { "<stdin>" } else { f.as_str() };
+        let read = read_text(f).and_then(|input| {
+            let locations: Vec<String> = reader::declaration_lines(&input)
+                .iter()
+                .map(|line| format!("{}:{}", name, line))
+                .collect();
+            let leases = parser::parse(input)
+                .map_err(|e| format!("{}: {}", name, e))?
+                .leases;
+            Ok((leases, locations))
+        });
+
+        let (leases, locations) = match read {
+            Ok(read) => read,
+            Err(e) => {
+                writeln!(out, "{}", e).map_err(|e| e.to_string())?;
+                errors += 1;
+                continue;
+            }
+        };
+
+        for finding in lint::validate(&ParserResult { leases }) {
+            match finding.severity() {
+                Severity::Error => errors += 1,
+                Severity::Warning => warnings += 1,
+            }
+            writeln!(out, "{}: {}", locations[finding.lease], finding)
+                .map_err(|e| e.to_string())?;
+        }
+    }
+
+    writeln!(out, "{} errors, {} warnings", errors, warnings).map_err(|e| e.to_string())?;
+    if errors > 0 || (strict && warnings > 0) {
+        return Ok(1);
+    }
+
+    Ok(0)
+}
@@ -1,7 +1,14 @@
 use std::fmt;
 use std::cmp;
+use std::hash::{Hash, Hasher};
+use std::time::{SystemTime, UNIX_EPOCH};
 
-#[derive(Clone, Debug, PartialEq, Eq, Copy)]
+/// Latest year [`Date::from`] accepts, the last one RFC3339 can represent
+pub const MAX_YEAR: i64 = 9999;
+
+/// Date and time in UTC. The weekday is not compared or hashed, it follows
+/// from the date.
+#[derive(Clone, Debug, Copy)]
 pub struct Date {
     pub weekday: i64,
     pub year: i64,
@@ -21,7 +28,7 @@ impl Date {
         // Parses from `weekday year/month/day hour:minute:second` format as
         // specified in OpenBSD man page
         let mut result = Date::new();
-        result.weekday = weekday.parse::<i64>().expect("Error parsing weekday");
+        result.weekday = weekday.parse::<i64>().map_err(|_| "Error parsing weekday")?;
         if result.weekday < 0 || result.weekday > 6 {
             return Err(format!("Weekday should be a number between 0 and 6. {} is not", weekday));
         }
@@ -30,30 +37,33 @@ impl Date {
         if d.len() != 3 {
             return Err(format!("{} does not have expected date format (YYYY/MM/DD)", date));
         }
-        result.year = d[0].to_string().parse::<i64>().expect("Year should be a number");
-        result.month = d[1].to_string().parse::<i64>().expect("Month should be a number");
-        if result.month < 1 {
-            return Err(format!("Month should be a number >= 1. {} is not", result.month));
+        result.year = d[0].to_string().parse::<i64>().map_err(|_| "Year should be a number")?;
+        if result.year < 0 || result.year > MAX_YEAR {
+            return Err(format!("Year should be a number between 0 and {}. {} is not", MAX_YEAR, result.year));
+        }
+        result.month = d[1].to_string().parse::<i64>().map_err(|_| "Month should be a number")?;
+        if result.month < 1 || result.month > 12 {
+            return Err(format!("Month should be a number between 1 and 12. {} is not", result.month));
         }
-        result.day = d[2].to_string().parse::<i64>().expect("Day should be a number");
-        if result.day < 1 {
-            return Err(format!("Day should be a number between >= 1. {} is not", result.day));
+        result.day = d[2].to_string().parse::<i64>().map_err(|_| "Day should be a number")?;
+        if result.day < 1 || result.day > 31 {
+            return Err(format!("Day should be a number between 1 and 31. {} is not", result.day));
         }
 
         let t: Vec<&str> = time.split(':').collect();
         if t.len() != 3 {
             return Err(format!("{} does not have expected time format (HH:mm:ss)", time));
         }
-        result.hour = t[0].to_string().parse::<i64>().expect("Hour should be a number");
+        result.hour = t[0].to_string().parse::<i64>().map_err(|_| "Hour should be a number")?;
         if result.hour < 0 || result.hour > 23 {
             return Err(format!("Hour should be a number between 0 and 23. {} is not", result.hour));
         }
-        result.minute = t[1].to_string().parse::<i64>().expect("Minute should be a number");
-        if result.minute < 0 || result.hour > 59 {
+        result.minute = t[1].to_string().parse::<i64>().map_err(|_| "Minute should be a number")?;
+        if result.minute < 0 || result.minute > 59 {
             return Err(format!("Minute should be a number between 0 and 59. {} is not", result.minute));
         }
-        result.second = t[2].to_string().parse::<i64>().expect("Second should be a number");
-        if result.hour < 0 || result.hour > 59 {
+        result.second = t[2].to_string().parse::<i64>().map_err(|_| "Second should be a number")?;
+        if result.second < 0 || result.second > 59 {
             return Err(format!("Second should be a number between 0 and 59. {} is not", result.second));
         }
 
@@ -75,6 +85,96 @@ impl Date {
         Date::from(weekday.to_string(), date, time)
     }
 
+    /// Creates `Date` from seconds since the Unix epoch
+    pub fn from_timestamp(ts: i64) -> Date {
+        let days = ts.div_euclid(86400);
+        let secs = ts.rem_euclid(86400);
+
+        // Civil from days algorithm, see
+        // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+        let z = days + 719468;
+        let era = z.div_euclid(146097);
+        let doe = z.rem_euclid(146097);
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+        Date {
+            weekday: (days + 4).rem_euclid(7),
+            year,
+            month,
+            day,
+            hour: secs / 3600,
+            minute: secs % 3600 / 60,
+            second: secs % 60,
+        }
+    }
+
+    /// Returns current UTC time
+    pub fn now() -> Date {
+        let ts = match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(d) => d.as_secs() as i64,
+            Err(e) => -(e.duration().as_secs() as i64),
+        };
+
+        Date::from_timestamp(ts)
+    }
+
+    /// Returns number of seconds since the Unix epoch, or `None` if a field is
+    /// outside of the range [`Date::from`] accepts. The weekday is ignored.
+    pub fn checked_timestamp(&self) -> Option<i64> {
+        let valid = (0..=MAX_YEAR).contains(&self.year)
+            && (1..=12).contains(&self.month)
+            && (1..=31).contains(&self.day)
+            && (0..=23).contains(&self.hour)
+            && (0..=59).contains(&self.minute)
+            && (0..=59).contains(&self.second);
+
+        if valid {
+            Some(self.timestamp())
+        } else {
+            None
+        }
+    }
+
+    /// Returns number of seconds since the Unix epoch. The weekday is ignored.
+    /// Dates from [`Date::from`] and [`Date::from_timestamp`] never overflow,
+    /// use [`Date::checked_timestamp`] for dates with fields set directly.
+    pub fn timestamp(&self) -> i64 {
+        // Days from civil algorithm, see
+        // http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+        let y = if self.month <= 2 { self.year - 1 } else { self.year };
+        let era = y.div_euclid(400);
+        let yoe = y.rem_euclid(400);
+        let mp = (self.month + 9) % 12;
+        let doy = (153 * mp + 2) / 5 + self.day - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        let days = era * 146097 + doe - 719468;
+
+        days * 86400 + self.hour * 3600 + self.minute * 60 + self.second
+    }
+
+    /// Formats the date as UTC datetime in RFC3339 format, e.g.
+    /// `2019-01-01T22:00:00Z`. The weekday is not part of the format.
+    pub fn to_rfc3339(&self) -> String {
+        format!(
+            "{:0>4}-{:0>2}-{:0>2}T{:0>2}:{:0>2}:{:0>2}Z",
+            self.year, self.month, self.day, self.hour, self.minute, self.second,
+        )
+    }
+
+    /// Formats the date the way it is written in `dhcpd.leases`, e.g.
+    /// `2 2019/01/01 22:00:00`
+    pub fn to_leases_format(&self) -> String {
+        format!(
+            "{} {}/{:0>2}/{:0>2} {:0>2}:{:0>2}:{:0>2}",
+            self.weekday, self.year, self.month, self.day, self.hour, self.minute, self.second,
+        )
+    }
+
     pub fn new() -> Date {
         Date {
             weekday: 0,
@@ -115,38 +215,88 @@ impl fmt::Display for Date {
     }
 }
 
-impl cmp::PartialOrd for Date {
-    fn partial_cmp(&self, other: &Date) -> Option<cmp::Ordering> {
-        if self.year != other.year {
-            return self.year.partial_cmp(&other.year);
-        }
-
-        if self.month != other.month {
-            return self.month.partial_cmp(&other.month);
-        }
+impl Default for Date {
+    fn default() -> Date {
+        Date::new()
+    }
+}
 
-        if self.day != other.day {
-            return self.day.partial_cmp(&other.day);
-        }
+impl Date {
+    fn key(&self) -> (i64, i64, i64, i64, i64, i64) {
+        (self.year, self.month, self.day, self.hour, self.minute, self.second)
+    }
+}
 
-        if self.hour != other.hour {
-            return self.hour.partial_cmp(&other.hour);
-        }
+impl PartialEq for Date {
+    fn eq(&self, other: &Date) -> bool {
+        self.key() == other.key()
+    }
+}
 
-        if self.minute != other.minute {
-            return self.minute.partial_cmp(&other.minute);
-        }
+impl Eq for Date {}
 
-        if self.second != other.second {
-            return self.second.partial_cmp(&other.second);
-        }
+impl Hash for Date {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
 
-        None
+impl cmp::PartialOrd for Date {
+    fn partial_cmp(&self, other: &Date) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
     }
 }
 
 impl cmp::Ord for Date {
     fn cmp(&self, other: &Date) -> cmp::Ordering {
-        return self.partial_cmp(other).unwrap();
+        self.key().cmp(&other.key())
+    }
+}
+
+/// Serialized as RFC3339 string, see [`Date::to_rfc3339`]
+#[cfg(feature = "serde")]
+impl serde::Serialize for Date {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_rfc3339())
+    }
+}
+
+/// Deserialized from RFC3339 string, converted to UTC. The weekday is
+/// computed from the date.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Date {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Date, D::Error> {
+        let s = <String as serde::Deserialize>::deserialize(deserializer)?;
+        let d = Date::from_rfc3339(0, &s).map_err(serde::de::Error::custom)?;
+        let offset = rfc3339_offset(&s).map_err(serde::de::Error::custom)?;
+
+        Ok(Date::from_timestamp(d.timestamp() - offset))
+    }
+}
+
+/// Returns the offset from UTC in seconds of an RFC3339 datetime, which
+/// [`Date::from_rfc3339`] ignores
+#[cfg(feature = "serde")]
+fn rfc3339_offset(input: &str) -> Result<i64, String> {
+    let time = input.split_once('T').map_or("", |(_, t)| t);
+    // skips `HH:MM:SS` and fractions of a second
+    let zone = time
+        .get(8..)
+        .unwrap_or("")
+        .trim_start_matches(|c: char| c == '.' || c.is_ascii_digit());
+
+    let sign = match zone.chars().next() {
+        Some('Z') | Some('z') if zone.len() == 1 => return Ok(0),
+        Some('+') => 1,
+        Some('-') => -1,
+        _ => return Err(format!("{:?} has no valid UTC offset", input)),
+    };
+    let hm: Vec<&str> = zone[1..].split(':').collect();
+    match hm.as_slice() {
+        [h, m] if h.len() == 2 && m.len() == 2 => match (h.parse::<i64>(), m.parse::<i64>()) {
+            (Ok(h), Ok(m)) if h < 24 && m < 60 => Ok(sign * (h * 3600 + m * 60)),
+            _ => Err(format!("{:?} has no valid UTC offset", input)),
+        },
+        _ => Err(format!("{:?} has no valid UTC offset", input)),
     }
 }
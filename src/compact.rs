@@ -0,0 +1,92 @@
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use crate::common::Date;
+use crate::leases::Leases;
+use crate::leases::LeasesMethods;
+use crate::parser;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CompactOptions {
+    /// Drop leases that ended more than this many days ago
+    pub drop_expired_after_days: Option<i64>,
+}
+
+/// Reduces the leases journal to the last declaration of every address, the
+/// same way dhcpd does when it rewrites the leases file.
+pub fn compact(leases: &Leases, options: &CompactOptions, now: Date) -> Leases {
+    let threshold = options
+        .drop_expired_after_days
+        .map(|days| now.timestamp() - days * 86400);
+
+    let mut res = Leases::new();
+    for l in leases.current().iter() {
+        if let (Some(threshold), Some(ends)) = (threshold, l.dates.ends) {
+            if ends.timestamp() < threshold {
+                continue;
+            }
+        }
+        res.push(l.clone());
+    }
+
+    res
+}
+
+/// Writes leases in the `dhcpd.leases` format
+pub fn write_leases<W: Write>(w: &mut W, leases: &Leases) -> Result<(), String> {
+    for l in leases.iter() {
+        writeln!(w, "{}", l).map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
+/// Compacts the leases file at `path` in place.
+///
+/// Compacted leases are written into a temporary file in the same directory
+/// which then replaces the original. The original file is kept as a backup
+/// with `~` appended to its name, like `dhcpd.leases~`.
+pub fn compact_file<P: AsRef<Path>>(path: P, options: &CompactOptions) -> Result<Leases, String> {
+    let path = path.as_ref();
+
+    let input = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let leases = compact(&parser::parse(input)?.leases, options, Date::now());
+
+    let tmp = sibling_path(path, &format!(".{}", process::id()));
+    let res = write_tmp(&tmp, &leases).and_then(|_| replace_with_backup(path, &tmp));
+    if res.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    res?;
+
+    Ok(leases)
+}
+
+fn write_tmp(tmp: &Path, leases: &Leases) -> Result<(), String> {
+    let mut f = fs::File::create(tmp).map_err(|e| format!("{}: {}", tmp.display(), e))?;
+    write_leases(&mut f, leases)?;
+    f.sync_all().map_err(|e| format!("{}: {}", tmp.display(), e))
+}
+
+fn replace_with_backup(path: &Path, tmp: &Path) -> Result<(), String> {
+    let backup = sibling_path(path, "~");
+    if backup.exists() {
+        fs::remove_file(&backup).map_err(|e| format!("{}: {}", backup.display(), e))?;
+    }
+
+    // Linking keeps the original file in place until the rename below, so
+    // there is no moment without a leases file
+    if fs::hard_link(path, &backup).is_err() {
+        fs::copy(path, &backup).map_err(|e| format!("{}: {}", backup.display(), e))?;
+    }
+
+    fs::rename(tmp, path).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn sibling_path(path: &Path, suffix: &str) -> PathBuf {
+    let mut s = path.as_os_str().to_owned();
+    s.push(suffix);
+    PathBuf::from(s)
+}
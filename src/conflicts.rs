@@ -0,0 +1,205 @@
+//! Detection of addresses bound to several clients at once and of hardware
+//! addresses holding several addresses at once.
+//!
+//! A lease is held from its `starts` up to, not including, its `ends`, as in
+//! the L006 check of [`validate`](crate::validate), so a client taking over an
+//! address the second the previous lease ends is not a conflict. Abandoned
+//! leases are not held by anyone. A declaration is replaced by a later
+//! declaration of the same address and client from the later one's `starts`
+//! on, so renewals and early releases written to the journal are not
+//! conflicts. Clients are matched by their [`Lease::client_id`].
+
+use std::collections::HashMap;
+
+use crate::common::Date;
+use crate::leases::{ClientId, Lease, Leases};
+
+/// Period in which one IP address was bound to two different clients
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct IpConflict {
+    pub ip: String,
+    /// The clients, in ascending order
+    pub clients: [ClientId; 2],
+    /// Start of the period, `None` if unbounded
+    pub from: Option<Date>,
+    /// End of the period, `None` if unbounded
+    pub to: Option<Date>,
+    /// Indices of the declarations involved, in ascending order
+    pub leases: Vec<usize>,
+}
+
+/// Period in which one hardware address held two different IP addresses
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MacConflict {
+    pub mac: String,
+    /// The IP addresses, in ascending order
+    pub ips: [String; 2],
+    pub from: Option<Date>,
+    pub to: Option<Date>,
+    pub leases: Vec<usize>,
+}
+
+/// Conflicts ordered by address and start of the period
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Conflicts {
+    pub ips: Vec<IpConflict>,
+    pub macs: Vec<MacConflict>,
+}
+
+impl Conflicts {
+    pub fn is_empty(&self) -> bool {
+        self.ips.is_empty() && self.macs.is_empty()
+    }
+}
+
+/// Finds all conflicts in the leases journal
+pub fn detect(leases: &Leases) -> Conflicts {
+    let mut by_ip: HashMap<&str, Vec<Interval<ClientId>>> = HashMap::new();
+    let mut by_mac: HashMap<&str, Vec<Interval<String>>> = HashMap::new();
+
+    for (i, l, start, end) in held(leases) {
+        if let Some(client) = l.client_id() {
+            by_ip.entry(&l.ip).or_default().push(Interval {
+                start,
+                end,
+                label: client,
+                lease: i,
+            });
+        }
+        if let Some(hw) = &l.hardware {
+            by_mac.entry(&hw.mac).or_default().push(Interval {
+                start,
+                end,
+                label: l.ip.clone(),
+                lease: i,
+            });
+        }
+    }
+
+    let mut res = Conflicts::default();
+    for (ip, intervals) in by_ip {
+        let same_client = |a: usize, b: usize| leases[a].client_id() == leases[b].client_id();
+        for o in overlaps(intervals, same_client) {
+            res.ips.push(IpConflict {
+                ip: ip.to_owned(),
+                clients: o.labels,
+                from: date(o.start),
+                to: date(o.end),
+                leases: o.leases,
+            });
+        }
+    }
+    for (mac, intervals) in by_mac {
+        let same_ip = |a: usize, b: usize| leases[a].ip == leases[b].ip;
+        for o in overlaps(intervals, same_ip) {
+            res.macs.push(MacConflict {
+                mac: mac.to_owned(),
+                ips: o.labels,
+                from: date(o.start),
+                to: date(o.end),
+                leases: o.leases,
+            });
+        }
+    }
+
+    res.ips
+        .sort_by(|a, b| (&a.ip, a.from).cmp(&(&b.ip, b.from)));
+    res.macs
+        .sort_by(|a, b| (&a.mac, a.from).cmp(&(&b.mac, b.from)));
+
+    res
+}
+
+/// Returns the periods the leases were held for, as timestamps with
+/// unbounded ends at the extremes, see the [module](self) documentation
+pub(crate) fn held(leases: &Leases) -> Vec<(usize, &Lease, i64, i64)> {
+    let mut next: HashMap<(&str, Option<ClientId>), i64> = HashMap::new();
+    let mut res = Vec::new();
+
+    // walking backwards, `next` holds the start of the following declaration
+    // of the same address and client
+    for (i, l) in leases.iter().enumerate().rev() {
+        let start = l.dates.starts.map_or(i64::MIN, |d| d.timestamp());
+        let mut end = l.dates.ends.map_or(i64::MAX, |d| d.timestamp());
+        if let Some(&replaced) = next.get(&(l.ip.as_str(), l.client_id())) {
+            end = end.min(replaced);
+        }
+        next.insert((l.ip.as_str(), l.client_id()), start);
+
+        if !l.abandoned && start <= end {
+            res.push((i, l, start, end));
+        }
+    }
+    res.reverse();
+
+    res
+}
+
+fn date(ts: i64) -> Option<Date> {
+    if ts == i64::MIN || ts == i64::MAX {
+        return None;
+    }
+
+    Some(Date::from_timestamp(ts))
+}
+
+struct Interval<L> {
+    start: i64,
+    end: i64,
+    label: L,
+    lease: usize,
+}
+
+struct Overlap<L> {
+    labels: [L; 2],
+    start: i64,
+    end: i64,
+    leases: Vec<usize>,
+}
+
+/// Returns periods in which intervals of leases not `same` overlap, merging
+/// adjacent periods of the same two labels. Empty intervals never overlap.
+fn overlaps<L, F>(mut intervals: Vec<Interval<L>>, same: F) -> Vec<Overlap<L>>
+where
+    L: Ord + Clone,
+    F: Fn(usize, usize) -> bool,
+{
+    intervals.retain(|i| i.start < i.end);
+    intervals.sort_by_key(|i| (i.start, i.lease));
+
+    let mut found = Vec::new();
+    for (n, a) in intervals.iter().enumerate() {
+        for b in intervals[n + 1..].iter().take_while(|b| b.start < a.end) {
+            if same(a.lease, b.lease) {
+                continue;
+            }
+
+            let mut labels = [a.label.clone(), b.label.clone()];
+            labels.sort();
+            let mut leases = vec![a.lease, b.lease];
+            leases.sort_unstable();
+            found.push(Overlap {
+                labels,
+                start: b.start,
+                end: a.end.min(b.end),
+                leases,
+            });
+        }
+    }
+    found.sort_by(|a, b| (&a.labels, a.start).cmp(&(&b.labels, b.start)));
+
+    let mut res: Vec<Overlap<L>> = Vec::new();
+    for o in found {
+        match res.last_mut() {
+            Some(last) if last.labels == o.labels && o.start <= last.end => {
+                last.end = last.end.max(o.end);
+                last.leases.extend(o.leases);
+                last.leases.sort_unstable();
+                last.leases.dedup();
+            }
+            _ => res.push(o),
+        }
+    }
+
+    res
+}
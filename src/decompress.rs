@@ -0,0 +1,81 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{Cursor, Read};
+use std::path::Path;
+
+/// Compression format of the input, detected from its magic bytes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Xz,
+    Zstd,
+}
+
+const MAGIC_LEN: usize = 6;
+
+impl Compression {
+    pub fn detect(magic: &[u8]) -> Compression {
+        if magic.starts_with(&[0x1f, 0x8b]) {
+            Compression::Gzip
+        } else if magic.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+            Compression::Xz
+        } else if magic.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Compression::Zstd
+        } else {
+            Compression::None
+        }
+    }
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Compression::None => "none",
+            Compression::Gzip => "gzip",
+            Compression::Xz => "xz",
+            Compression::Zstd => "zstd",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Wraps the reader in a decoder matching the compression of its content.
+///
+/// Each compression format is supported only when the cargo feature of the
+/// same name is enabled. Uncompressed input is passed through.
+pub fn decompress<'r, R: Read + 'r>(mut reader: R) -> Result<Box<dyn Read + 'r>, String> {
+    let mut magic = Vec::with_capacity(MAGIC_LEN);
+    (&mut reader)
+        .take(MAGIC_LEN as u64)
+        .read_to_end(&mut magic)
+        .map_err(|e| e.to_string())?;
+
+    let compression = Compression::detect(&magic);
+    let reader = Cursor::new(magic).chain(reader);
+
+    match compression {
+        Compression::None => Ok(Box::new(reader)),
+        #[cfg(feature = "gzip")]
+        Compression::Gzip => Ok(Box::new(flate2::read::MultiGzDecoder::new(reader))),
+        #[cfg(feature = "xz")]
+        Compression::Xz => Ok(Box::new(xz2::read::XzDecoder::new_multi_decoder(reader))),
+        #[cfg(feature = "zstd")]
+        Compression::Zstd => Ok(Box::new(
+            zstd::stream::read::Decoder::new(reader).map_err(|e| e.to_string())?,
+        )),
+        #[allow(unreachable_patterns)]
+        c => Err(format!(
+            "Input is {} compressed, which requires the `{}` feature",
+            c, c
+        )),
+    }
+}
+
+/// Opens the file at `path` for reading, decompressing it if necessary
+pub fn open<P: AsRef<Path>>(path: P) -> Result<Box<dyn Read>, String> {
+    let path = path.as_ref();
+    let file = File::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    decompress(file).map_err(|e| format!("{}: {}", path.display(), e))
+}
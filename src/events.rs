@@ -0,0 +1,149 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::common::Date;
+use crate::leases::Lease;
+use crate::leases::LeaseState;
+use crate::leases::Leases;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LeaseEventKind {
+    /// Address was leased to a client
+    Granted,
+    /// Same client extended the lease
+    Renewed,
+    /// Lease was dropped from the leases file, or ran out, see [`diff_at`]
+    Expired,
+    /// Same client ended the lease earlier than it was scheduled to end
+    Released,
+    /// Lease was marked as abandoned
+    Abandoned,
+    /// Address was leased to another client
+    Reassigned,
+}
+
+impl fmt::Display for LeaseEventKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            LeaseEventKind::Granted => "granted",
+            LeaseEventKind::Renewed => "renewed",
+            LeaseEventKind::Expired => "expired",
+            LeaseEventKind::Released => "released",
+            LeaseEventKind::Abandoned => "abandoned",
+            LeaseEventKind::Reassigned => "reassigned",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LeaseEvent {
+    pub kind: LeaseEventKind,
+    pub ip: String,
+    /// Current lease of the address in the old snapshot
+    pub old: Option<Lease>,
+    /// Current lease of the address in the new snapshot
+    pub new: Option<Lease>,
+}
+
+/// Classifies changes between two snapshots of the leases file.
+///
+/// Both snapshots are reduced to their current state first (see
+/// [`LeaseTable`](crate::table::LeaseTable)), so only the last declaration of
+/// every address is compared. Events for addresses of the new snapshot come
+/// first in its order, followed by [`LeaseEventKind::Expired`] events for
+/// addresses which are no longer present. Clients are compared by their
+/// [`Lease::client_id`].
+///
+/// Only changes of the file are reported: a lease running out while its
+/// declaration stays the same is not an event, use [`diff_at`] for that.
+pub fn diff(old: &Leases, new: &Leases) -> Vec<LeaseEvent> {
+    diff_with(old, new, None)
+}
+
+/// Like [`diff`] for snapshots taken at `then` and `now`, additionally
+/// reporting [`LeaseEventKind::Expired`] for unchanged leases which were
+/// active at `then` and ran out by `now`
+pub fn diff_at(old: &Leases, new: &Leases, then: Date, now: Date) -> Vec<LeaseEvent> {
+    diff_with(old, new, Some((then, now)))
+}
+
+fn diff_with(old: &Leases, new: &Leases, times: Option<(Date, Date)>) -> Vec<LeaseEvent> {
+    let old_table = old.current();
+    let new_table = new.current();
+    let mut res = Vec::new();
+
+    for n in new_table.iter() {
+        let o = old_table.get(&n.ip);
+        let kind = match (classify(o, n), o, times) {
+            (None, Some(o), Some((then, now)))
+                if o.state_at(then) == LeaseState::Active
+                    && n.state_at(now) == LeaseState::Expired =>
+            {
+                Some(LeaseEventKind::Expired)
+            }
+            (kind, _, _) => kind,
+        };
+        if let Some(kind) = kind {
+            res.push(LeaseEvent {
+                kind,
+                ip: n.ip.clone(),
+                old: o.cloned(),
+                new: Some(n.clone()),
+            });
+        }
+    }
+
+    for o in old_table.iter() {
+        if new_table.get(&o.ip).is_none() {
+            res.push(LeaseEvent {
+                kind: LeaseEventKind::Expired,
+                ip: o.ip.clone(),
+                old: Some(o.clone()),
+                new: None,
+            });
+        }
+    }
+
+    res
+}
+
+fn classify(old: Option<&Lease>, new: &Lease) -> Option<LeaseEventKind> {
+    let old = match old {
+        Some(o) if o == new => return None,
+        Some(o) => o,
+        None if new.abandoned => return Some(LeaseEventKind::Abandoned),
+        None => return Some(LeaseEventKind::Granted),
+    };
+
+    if new.abandoned {
+        if old.abandoned {
+            return None;
+        }
+        return Some(LeaseEventKind::Abandoned);
+    }
+
+    if old.client_id() != new.client_id() {
+        return Some(LeaseEventKind::Reassigned);
+    }
+
+    if old.abandoned {
+        return Some(LeaseEventKind::Granted);
+    }
+
+    match cmp_ends(old.dates.ends, new.dates.ends) {
+        Ordering::Less => Some(LeaseEventKind::Renewed),
+        Ordering::Greater => Some(LeaseEventKind::Released),
+        Ordering::Equal => None,
+    }
+}
+
+/// Compares lease ends where a missing date means the lease never ends
+fn cmp_ends(a: Option<Date>, b: Option<Date>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Write;
+
+use crate::common::Date;
+use crate::leases::Lease;
+use crate::leases::Leases;
+
+/// Column of the exported leases
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Column {
+    Ip,
+    Mac,
+    Uid,
+    Hostname,
+    ClientHostname,
+    Starts,
+    Ends,
+    Abandoned,
+    /// State of the lease at [`ExportOptions::now`], see
+    /// [`LeaseState`](crate::leases::LeaseState)
+    State,
+}
+
+impl Column {
+    pub const ALL: [Column; 9] = [
+        Column::Ip,
+        Column::Mac,
+        Column::Uid,
+        Column::Hostname,
+        Column::ClientHostname,
+        Column::Starts,
+        Column::Ends,
+        Column::Abandoned,
+        Column::State,
+    ];
+
+    pub fn from(s: &str) -> Result<Column, String> {
+        Column::ALL
+            .iter()
+            .find(|c| c.name() == s)
+            .copied()
+            .ok_or(format!("'{}' is not a known column", s))
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Column::Ip => "ip",
+            Column::Mac => "mac",
+            Column::Uid => "uid",
+            Column::Hostname => "hostname",
+            Column::ClientHostname => "client-hostname",
+            Column::Starts => "starts",
+            Column::Ends => "ends",
+            Column::Abandoned => "abandoned",
+            Column::State => "state",
+        }
+    }
+
+    /// Returns value of the column for `l`, dates are formatted as RFC3339 and
+    /// `abandoned` as `true` or `false`
+    pub fn value(&self, l: &Lease, now: Date) -> Option<String> {
+        match self {
+            Column::Ip => Some(l.ip.clone()),
+            Column::Mac => l.hardware.as_ref().map(|hw| hw.mac.clone()),
+            Column::Uid => l.uid.clone(),
+            Column::Hostname => l.hostname.clone(),
+            Column::ClientHostname => l.client_hostname.clone(),
+            Column::Starts => l.dates.starts.map(|d| d.to_rfc3339()),
+            Column::Ends => l.dates.ends.map(|d| d.to_rfc3339()),
+            Column::Abandoned => Some(l.abandoned.to_string()),
+            Column::State => Some(l.state_at(now).to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Column {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExportOptions {
+    pub columns: Vec<Column>,
+    /// Export only the last declaration of every address, see
+    /// [`LeaseTable`](crate::table::LeaseTable)
+    pub current_only: bool,
+    pub now: Date,
+}
+
+impl Default for ExportOptions {
+    fn default() -> ExportOptions {
+        ExportOptions {
+            columns: Column::ALL.to_vec(),
+            current_only: false,
+            now: Date::now(),
+        }
+    }
+}
+
+/// Writes leases as a JSON array of objects in the layout of the serde
+/// representation of [`Lease`], restricted to the selected columns.
+///
+/// `starts` and `ends` are nested in `dates`, the `mac` column is written as
+/// `hardware` with the hardware type, `client-hostname` as `client_hostname`
+/// and `abandoned` as a boolean. `state` is added last. With every column
+/// but `state` selected, objects deserialize back into leases.
+pub fn write_json<W: Write>(
+    w: &mut W,
+    leases: &Leases,
+    options: &ExportOptions,
+) -> Result<(), String> {
+    let rows: Vec<String> = selected(leases, options)
+        .iter()
+        .map(|l| json_object(l, options))
+        .collect();
+
+    if rows.is_empty() {
+        return writeln!(w, "[]").map_err(|e| e.to_string());
+    }
+    writeln!(w, "[\n  {}\n]", rows.join(",\n  ")).map_err(|e| e.to_string())
+}
+
+/// Writes leases as newline delimited JSON, one object per line, see
+/// [`write_json`]
+pub fn write_ndjson<W: Write>(
+    w: &mut W,
+    leases: &Leases,
+    options: &ExportOptions,
+) -> Result<(), String> {
+    for l in selected(leases, options).iter() {
+        writeln!(w, "{}", json_object(l, options)).map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
+/// Writes leases as CSV with a header row, missing values are empty
+pub fn write_csv<W: Write>(
+    w: &mut W,
+    leases: &Leases,
+    options: &ExportOptions,
+) -> Result<(), String> {
+    let header: Vec<&str> = options.columns.iter().map(|c| c.name()).collect();
+    write!(w, "{}\r\n", header.join(",")).map_err(|e| e.to_string())?;
+
+    for l in selected(leases, options).iter() {
+        let row: Vec<String> = options
+            .columns
+            .iter()
+            .map(|c| csv_field(&c.value(l, options.now).unwrap_or_default()))
+            .collect();
+        write!(w, "{}\r\n", row.join(",")).map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
+/// Returns the leases to export, with the current ones selected like
+/// [`LeaseTable`](crate::table::LeaseTable) does but without copying them
+fn selected<'a>(leases: &'a Leases, options: &ExportOptions) -> Vec<&'a Lease> {
+    if !options.current_only {
+        return leases.iter().collect();
+    }
+
+    let mut last = HashMap::new();
+    for (i, l) in leases.iter().enumerate() {
+        last.insert(l.ip.as_str(), i);
+    }
+
+    leases
+        .iter()
+        .enumerate()
+        .filter(|&(i, l)| last[l.ip.as_str()] == i)
+        .map(|(_, l)| l)
+        .collect()
+}
+
+fn json_object(l: &Lease, options: &ExportOptions) -> String {
+    let has = |c: Column| options.columns.contains(&c);
+    let date = |d: Option<Date>| json_option(d.map(|d| d.to_rfc3339()).as_deref());
+
+    let mut fields = Vec::new();
+    if has(Column::Ip) {
+        fields.push(json_field("ip", json_string(&l.ip)));
+    }
+    if has(Column::Starts) || has(Column::Ends) {
+        let mut dates = Vec::new();
+        if has(Column::Starts) {
+            dates.push(json_field("starts", date(l.dates.starts)));
+        }
+        if has(Column::Ends) {
+            dates.push(json_field("ends", date(l.dates.ends)));
+        }
+        fields.push(json_field("dates", format!("{{{}}}", dates.join(","))));
+    }
+    if has(Column::Mac) {
+        let hardware = match &l.hardware {
+            Some(hw) => format!(
+                "{{{},{}}}",
+                json_field("h_type", json_string(&hw.h_type)),
+                json_field("mac", json_string(&hw.mac))
+            ),
+            None => "null".to_owned(),
+        };
+        fields.push(json_field("hardware", hardware));
+    }
+    if has(Column::Uid) {
+        fields.push(json_field("uid", json_option(l.uid.as_deref())));
+    }
+    if has(Column::ClientHostname) {
+        let hn = json_option(l.client_hostname.as_deref());
+        fields.push(json_field("client_hostname", hn));
+    }
+    if has(Column::Hostname) {
+        fields.push(json_field("hostname", json_option(l.hostname.as_deref())));
+    }
+    if has(Column::Abandoned) {
+        fields.push(json_field("abandoned", l.abandoned.to_string()));
+    }
+    if has(Column::State) {
+        let state = l.state_at(options.now).to_string();
+        fields.push(json_field("state", json_string(&state)));
+    }
+
+    format!("{{{}}}", fields.join(","))
+}
+
+fn json_field(name: &str, value: String) -> String {
+    format!("{}:{}", json_string(name), value)
+}
+
+fn json_option(value: Option<&str>) -> String {
+    match value {
+        Some(v) => json_string(v),
+        None => "null".to_owned(),
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut res = String::with_capacity(s.len() + 2);
+    res.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            '\n' => res.push_str("\\n"),
+            '\r' => res.push_str("\\r"),
+            '\t' => res.push_str("\\t"),
+            c if (c as u32) < 0x20 => res.push_str(&format!("\\u{:04x}", c as u32)),
+            c => res.push(c),
+        }
+    }
+    res.push('"');
+
+    res
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_owned()
+    }
+}
@@ -0,0 +1,178 @@
+use std::fs::{self, File, Metadata};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use crate::leases::Lease;
+use crate::leases::Leases;
+use crate::leases::LeasesMethods;
+use crate::parser;
+use crate::reader::BlockBuffer;
+
+/// Leases read by a single [`LeaseFollower::poll`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FollowBatch {
+    /// Newly appended leases or, after a resync, all leases of the file
+    pub leases: Vec<Lease>,
+    /// The file was replaced or truncated and has been read from the start
+    pub resynced: bool,
+}
+
+/// Offset and partial input of a followed file, shared by the blocking and
+/// the async follower
+#[derive(Debug, Default)]
+pub(crate) struct FollowState {
+    file_id: Option<u64>,
+    offset: u64,
+    buffer: BlockBuffer,
+    leases: Leases,
+}
+
+impl FollowState {
+    pub(crate) fn new(file_id: Option<u64>) -> FollowState {
+        FollowState {
+            file_id,
+            ..FollowState::default()
+        }
+    }
+
+    pub(crate) fn leases(&self) -> &Leases {
+        &self.leases
+    }
+
+    pub(crate) fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Checks whether the file at the followed path was replaced or truncated
+    pub(crate) fn needs_resync(&self, meta: &Metadata) -> bool {
+        file_id(meta) != self.file_id || meta.len() < self.offset
+    }
+
+    pub(crate) fn reset(&mut self, file_id: Option<u64>) {
+        *self = FollowState::new(file_id);
+    }
+
+    /// Consumes bytes read at the current offset and returns leases they
+    /// complete
+    pub(crate) fn feed(&mut self, bytes: &[u8]) -> Result<Vec<Lease>, String> {
+        self.offset += bytes.len() as u64;
+        self.buffer.push(bytes)?;
+
+        let text = match self.buffer.take_complete() {
+            Some(t) => t,
+            None => return Ok(Vec::new()),
+        };
+
+        let parsed = parser::parse(text)?.leases;
+        for l in parsed.iter() {
+            self.leases.push(l.clone());
+        }
+
+        Ok(parsed.all())
+    }
+}
+
+/// Follows a live, append-only `dhcpd.leases` file.
+///
+/// The file is parsed once when opened, afterwards only bytes appended since
+/// the last read are parsed. A declaration that is still being written is kept
+/// until it is complete. When dhcpd rewrites the leases file (a new inode
+/// replaces the old one) or the file shrinks, the follower reads it again from
+/// the start.
+#[derive(Debug)]
+pub struct LeaseFollower {
+    path: PathBuf,
+    file: File,
+    state: FollowState,
+}
+
+impl LeaseFollower {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<LeaseFollower, String> {
+        let path = path.as_ref().to_path_buf();
+        let (file, file_id) = open_file(&path)?;
+
+        let mut follower = LeaseFollower {
+            path,
+            file,
+            state: FollowState::new(file_id),
+        };
+        follower.read_appended()?;
+
+        Ok(follower)
+    }
+
+    /// Returns all leases read so far
+    pub fn leases(&self) -> &Leases {
+        self.state.leases()
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Reads leases appended since the last call without blocking
+    pub fn poll(&mut self) -> Result<FollowBatch, String> {
+        // a missing file means dhcpd is in the middle of replacing it
+        let resynced = match fs::metadata(&self.path) {
+            Ok(meta) => self.state.needs_resync(&meta),
+            Err(_) => false,
+        };
+        if resynced {
+            let (file, file_id) = open_file(&self.path)?;
+            self.file = file;
+            self.state.reset(file_id);
+        }
+
+        let leases = self.read_appended()?;
+
+        Ok(FollowBatch { leases, resynced })
+    }
+
+    /// Blocks until some leases are appended, checking the file every
+    /// `interval`
+    pub fn wait(&mut self, interval: Duration) -> Result<FollowBatch, String> {
+        loop {
+            let batch = self.poll()?;
+            if batch.resynced || !batch.leases.is_empty() {
+                return Ok(batch);
+            }
+            thread::sleep(interval);
+        }
+    }
+
+    fn read_appended(&mut self) -> Result<Vec<Lease>, String> {
+        self.file
+            .seek(SeekFrom::Start(self.state.offset()))
+            .map_err(|e| format!("{}: {}", self.path.display(), e))?;
+
+        let mut bytes = Vec::new();
+        self.file
+            .read_to_end(&mut bytes)
+            .map_err(|e| format!("{}: {}", self.path.display(), e))?;
+
+        self.state.feed(&bytes)
+    }
+}
+
+fn open_file(path: &Path) -> Result<(File, Option<u64>), String> {
+    let file = File::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let meta = file
+        .metadata()
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    Ok((file, file_id(&meta)))
+}
+
+#[cfg(unix)]
+pub(crate) fn file_id(meta: &Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+
+    Some(meta.ino())
+}
+
+#[cfg(not(unix))]
+pub(crate) fn file_id(_meta: &Metadata) -> Option<u64> {
+    None
+}
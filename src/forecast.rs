@@ -0,0 +1,168 @@
+//! Forecasting of address pool exhaustion from the recent history of a pool.
+//!
+//! The number of active leases of the pool, as in [`stats`](crate::stats), is
+//! sampled over [`ForecastOptions::window`]. A line is fitted through the
+//! highest number of every day by least squares and the pool is expected to
+//! run out when the line reaches its size.
+
+use std::convert::TryFrom;
+use std::net::IpAddr;
+use std::time::Duration;
+
+use crate::common::Date;
+use crate::leases::{LeaseState, Leases, LeasesMethods};
+use crate::net::AddressRange;
+use crate::stats::active_samples;
+
+const DAY: i64 = 86400;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ForecastOptions {
+    /// Period before `now` the trend is fitted to
+    pub window: Duration,
+    /// Interval between the samples of the number of active leases
+    pub resolution: Duration,
+    pub now: Date,
+}
+
+impl Default for ForecastOptions {
+    fn default() -> ForecastOptions {
+        ForecastOptions {
+            window: Duration::from_secs(7 * DAY as u64),
+            resolution: Duration::from_secs(3600),
+            now: Date::now(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Forecast {
+    pub pool: AddressRange,
+    /// Number of addresses of the pool
+    pub size: u128,
+    /// Leases of the pool active at `now`
+    pub active: usize,
+    /// Highest sampled number of active leases in the window
+    pub peak: usize,
+    /// Highest sampled number of active leases by hour of the day, UTC
+    pub hourly_peaks: [usize; 24],
+    /// Hour of the day with the highest peak, `None` without active leases
+    pub peak_hour: Option<u8>,
+    /// Addresses left free at the peak
+    pub headroom: u128,
+    /// Growth of the daily peak in addresses per day, `None` with less than
+    /// two days of samples
+    pub growth: Option<f64>,
+    /// Expected date the daily peak reaches the size of the pool, `now` if it
+    /// did already and `None` if it is not growing
+    pub exhaustion: Option<Date>,
+}
+
+impl Forecast {
+    /// Returns the share of the pool in use at the peak
+    pub fn peak_utilisation(&self) -> f64 {
+        if self.size == 0 {
+            return 0.0;
+        }
+
+        self.peak as f64 / self.size as f64
+    }
+}
+
+/// Forecasts the exhaustion of the pool from the leases of its addresses.
+/// Fails if the window would have more than
+/// [`MAX_SAMPLES`](crate::stats::MAX_SAMPLES) samples.
+pub fn forecast(
+    leases: &Leases,
+    pool: AddressRange,
+    options: &ForecastOptions,
+) -> Result<Forecast, String> {
+    let mut own = Leases::new();
+    for l in leases.iter() {
+        if l.ip.parse::<IpAddr>().is_ok_and(|ip| pool.contains(&ip)) {
+            own.push(l.clone());
+        }
+    }
+
+    let now = options.now.timestamp();
+    let step = options.resolution.as_secs().max(1) as i64;
+    let from = i64::try_from(options.window.as_secs())
+        .ok()
+        .and_then(|window| now.checked_sub(window))
+        .ok_or_else(|| "forecast window is too long".to_owned())?;
+    let samples = active_samples(&own, from.div_euclid(step) * step, now, step)?;
+
+    let mut hourly_peaks = [0; 24];
+    let mut daily_peaks: Vec<(i64, usize)> = Vec::new();
+    for s in samples.iter() {
+        let hour = &mut hourly_peaks[s.at.hour as usize];
+        *hour = (*hour).max(s.active);
+
+        let day = s.at.timestamp().div_euclid(DAY);
+        match daily_peaks.last_mut() {
+            Some((d, peak)) if *d == day => *peak = (*peak).max(s.active),
+            _ => daily_peaks.push((day, s.active)),
+        }
+    }
+
+    let peak = hourly_peaks.iter().copied().max().unwrap_or(0);
+    let peak_hour = match peak {
+        0 => None,
+        _ => hourly_peaks
+            .iter()
+            .position(|&p| p == peak)
+            .map(|h| h as u8),
+    };
+
+    let size = pool.size();
+    let fit = fit(&daily_peaks);
+    let exhaustion = if peak as u128 >= size {
+        Some(options.now)
+    } else {
+        fit.and_then(|(intercept, slope)| {
+            if slope <= 0.0 {
+                return None;
+            }
+            let day = (size as f64 - intercept) / slope;
+            let at = (day * DAY as f64).min(i64::MAX as f64) as i64;
+            Some(Date::from_timestamp(at.max(now)))
+        })
+    };
+
+    Ok(Forecast {
+        pool,
+        size,
+        active: own
+            .current()
+            .iter()
+            .filter(|l| l.state_at(options.now) == LeaseState::Active)
+            .count(),
+        peak,
+        hourly_peaks,
+        peak_hour,
+        headroom: size.saturating_sub(peak as u128),
+        growth: fit.map(|(_, slope)| slope),
+        exhaustion,
+    })
+}
+
+/// Fits `peak = intercept + slope * day` by least squares, with days since
+/// the epoch
+fn fit(points: &[(i64, usize)]) -> Option<(f64, f64)> {
+    if points.len() < 2 {
+        return None;
+    }
+
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|&(x, _)| x as f64).sum::<f64>() / n;
+    let mean_y = points.iter().map(|&(_, y)| y as f64).sum::<f64>() / n;
+    let (mut cov, mut var) = (0.0, 0.0);
+    for &(x, y) in points {
+        let dx = x as f64 - mean_x;
+        cov += dx * (y as f64 - mean_y);
+        var += dx * dx;
+    }
+    let slope = cov / var;
+
+    Some((mean_y - slope * mean_x, slope))
+}
@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::decompress;
+use crate::leases::Lease;
+use crate::leases::Leases;
+use crate::leases::LeasesMethods;
+use crate::parser;
+use crate::reader::declaration_lines;
+
+/// Location of a lease declaration
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Provenance {
+    pub path: PathBuf,
+    /// Line of the `lease` keyword, starting at 1
+    pub line: usize,
+}
+
+/// Leases merged from several files, ordered by their `starts` date.
+///
+/// Identical declarations found in more than one file, which is common for
+/// backups and rotated copies of the same leases file, are kept only once
+/// together with all of their locations.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LeaseHistory {
+    leases: Leases,
+    provenance: Vec<Vec<Provenance>>,
+}
+
+impl LeaseHistory {
+    pub fn leases(&self) -> &Leases {
+        &self.leases
+    }
+
+    pub fn into_leases(self) -> Leases {
+        self.leases
+    }
+
+    /// Returns locations of the `i`-th lease
+    pub fn provenance(&self, i: usize) -> &[Provenance] {
+        &self.provenance[i]
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Lease, &[Provenance])> {
+        self.leases
+            .iter()
+            .zip(self.provenance.iter().map(|p| p.as_slice()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.leases.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.leases.is_empty()
+    }
+}
+
+/// Loads and merges the given leases files, compressed ones included.
+///
+/// Leases with equal `starts` keep the order of `paths`, so older files should
+/// come first.
+pub fn load_files<I, P>(paths: I) -> Result<LeaseHistory, String>
+where
+    I: IntoIterator<Item = P>,
+    P: AsRef<Path>,
+{
+    let mut found: Vec<(Lease, Vec<Provenance>)> = Vec::new();
+    let mut seen: HashMap<Lease, usize> = HashMap::new();
+
+    for path in paths {
+        let path = path.as_ref();
+        for (lease, line) in parse_with_lines(path)? {
+            let prov = Provenance {
+                path: path.to_path_buf(),
+                line,
+            };
+
+            match seen.get(&lease) {
+                Some(&i) => found[i].1.push(prov),
+                None => {
+                    seen.insert(lease.clone(), found.len());
+                    found.push((lease, vec![prov]));
+                }
+            }
+        }
+    }
+
+    // stable, so declarations without `starts` come first in file order
+    found.sort_by_key(|(l, _)| l.dates.starts);
+
+    let mut history = LeaseHistory::default();
+    for (l, prov) in found {
+        history.leases.push(l);
+        history.provenance.push(prov);
+    }
+
+    Ok(history)
+}
+
+/// Loads all files matching `pattern` and merges them with [`load_files`],
+/// oldest files first.
+///
+/// `pattern` is either a directory, in which case every file in it is loaded,
+/// or a path whose last component may contain `*` and `?` wildcards, like
+/// `/var/db/dhcpd.leases*`.
+pub fn load<P: AsRef<Path>>(pattern: P) -> Result<LeaseHistory, String> {
+    load_files(matching_files(pattern.as_ref())?)
+}
+
+fn matching_files(pattern: &Path) -> Result<Vec<PathBuf>, String> {
+    let (dir, name) = if pattern.is_dir() {
+        (pattern, "*".to_owned())
+    } else {
+        let dir = match pattern.parent() {
+            Some(d) if !d.as_os_str().is_empty() => d,
+            _ => Path::new("."),
+        };
+        let name = pattern
+            .file_name()
+            .ok_or(format!("{}: no file name to match", pattern.display()))?
+            .to_string_lossy()
+            .into_owned();
+        (dir, name)
+    };
+
+    let entries = fs::read_dir(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+
+    let mut files = Vec::new();
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("{}: {}", dir.display(), e))?;
+        let meta = entry
+            .metadata()
+            .map_err(|e| format!("{}: {}", entry.path().display(), e))?;
+
+        if meta.is_file() && matches_glob(&name, &entry.file_name().to_string_lossy()) {
+            files.push((meta.modified().ok(), entry.path()));
+        }
+    }
+    files.sort();
+
+    Ok(files.into_iter().map(|(_, p)| p).collect())
+}
+
+/// Matches `name` against a pattern with `*` and `?` wildcards
+fn matches_glob(pattern: &str, name: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let n: Vec<char> = name.chars().collect();
+
+    let (mut pi, mut ni) = (0, 0);
+    let mut backtrack = None;
+    while ni < n.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == n[ni]) {
+            pi += 1;
+            ni += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            backtrack = Some((pi, ni));
+            pi += 1;
+        } else if let Some((bp, bn)) = backtrack {
+            pi = bp + 1;
+            ni = bn + 1;
+            backtrack = Some((bp, bn + 1));
+        } else {
+            return false;
+        }
+    }
+
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+fn parse_with_lines(path: &Path) -> Result<Vec<(Lease, usize)>, String> {
+    let mut input = String::new();
+    decompress::open(path)?
+        .read_to_string(&mut input)
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    let lines = declaration_lines(&input);
+    let leases = parser::parse(input)
+        .map_err(|e| format!("{}: {}", path.display(), e))?
+        .leases;
+
+    Ok(leases.into_iter().zip(lines).collect())
+}
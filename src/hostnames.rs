@@ -0,0 +1,226 @@
+//! Owners of the hostnames and client hostnames announced in the leases.
+//!
+//! A client claims a name for the periods of its declarations announcing it,
+//! from their `starts` up to, not including, their `ends`, a missing date
+//! leaving the period unbounded. Names are compared case-insensitively and
+//! clients by their [`ClientId`]. Two clients collide when they claim the same
+//! name at a common time, so a name handed back and forth between clients is
+//! not a collision.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::common::Date;
+use crate::leases::{ClientId, Leases};
+
+/// Statement a name was announced in
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum NameSource {
+    Hostname,
+    ClientHostname,
+}
+
+impl fmt::Display for NameSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NameSource::Hostname => write!(f, "hostname"),
+            NameSource::ClientHostname => write!(f, "client-hostname"),
+        }
+    }
+}
+
+/// Client which claimed a name
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Claim {
+    pub client: ClientId,
+    /// Earliest `starts` of the declarations with the name, `None` if one of
+    /// them has no start
+    pub from: Option<Date>,
+    /// Latest `ends` of the declarations with the name, `None` if one of
+    /// them has no end
+    pub to: Option<Date>,
+    /// Periods the name was claimed in as `(starts, ends)`, in ascending
+    /// order, with overlapping or touching periods of the declarations merged
+    pub periods: Vec<(Option<Date>, Option<Date>)>,
+    /// Indices of the declarations with the name
+    pub leases: Vec<usize>,
+}
+
+impl Claim {
+    /// Returns whether both claims are held at a common time, see the
+    /// [module](self) documentation
+    pub fn overlaps(&self, other: &Claim) -> bool {
+        self.periods.iter().any(|&a| {
+            other
+                .periods
+                .iter()
+                .any(|&b| start(a) < end(b) && start(b) < end(a))
+        })
+    }
+}
+
+/// All clients which claimed a name, ordered by their `from`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct NameOwners {
+    /// The name in lowercase, as DNS names are case insensitive
+    pub name: String,
+    pub source: NameSource,
+    pub claims: Vec<Claim>,
+}
+
+impl NameOwners {
+    /// Returns whether more than one client claimed the name at the same time
+    pub fn is_collision(&self) -> bool {
+        !self.colliding().is_empty()
+    }
+
+    /// Returns claims overlapping the claim of another client
+    pub fn colliding(&self) -> Vec<&Claim> {
+        self.claims
+            .iter()
+            .enumerate()
+            .filter(|&(i, c)| {
+                self.claims
+                    .iter()
+                    .enumerate()
+                    .any(|(j, o)| i != j && c.overlaps(o))
+            })
+            .map(|(_, c)| c)
+            .collect()
+    }
+}
+
+/// Owners of every hostname and client hostname, ordered by name
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HostnameReport {
+    names: Vec<NameOwners>,
+}
+
+impl HostnameReport {
+    pub fn get<S: AsRef<str>>(&self, name: S, source: NameSource) -> Option<&NameOwners> {
+        let key = (name.as_ref().to_lowercase(), source);
+        self.names
+            .binary_search_by(|o| (&o.name, o.source).cmp(&(&key.0, key.1)))
+            .ok()
+            .map(|i| &self.names[i])
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, NameOwners> {
+        self.names.iter()
+    }
+
+    /// Returns names claimed by more than one client at the same time
+    pub fn collisions(&self) -> impl Iterator<Item = &NameOwners> {
+        self.names.iter().filter(|o| o.is_collision())
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+/// Collects owners of the names announced in the leases. Declarations
+/// without a hardware address or uid are left out.
+pub fn report(leases: &Leases) -> HostnameReport {
+    let mut names: BTreeMap<(String, NameSource), BTreeMap<ClientId, Claim>> = BTreeMap::new();
+
+    for (i, l) in leases.iter().enumerate() {
+        let client = match l.client_id() {
+            Some(c) => c,
+            None => continue,
+        };
+
+        for (name, source) in [
+            (&l.hostname, NameSource::Hostname),
+            (&l.client_hostname, NameSource::ClientHostname),
+        ]
+        .iter()
+        {
+            let name = match name {
+                Some(n) => n.to_lowercase(),
+                None => continue,
+            };
+
+            let claim = names
+                .entry((name, *source))
+                .or_default()
+                .entry(client.clone())
+                .or_insert_with(|| Claim {
+                    client: client.clone(),
+                    from: l.dates.starts,
+                    to: l.dates.ends,
+                    periods: Vec::new(),
+                    leases: Vec::new(),
+                });
+            claim.from = earliest(claim.from, l.dates.starts);
+            claim.to = latest(claim.to, l.dates.ends);
+            claim.periods.push((l.dates.starts, l.dates.ends));
+            claim.leases.push(i);
+        }
+    }
+
+    let names = names
+        .into_iter()
+        .map(|((name, source), claims)| {
+            let mut claims: Vec<Claim> = claims.into_values().collect();
+            for c in claims.iter_mut() {
+                c.periods = merge(std::mem::take(&mut c.periods));
+            }
+            claims.sort_by_key(|c| c.from);
+            NameOwners {
+                name,
+                source,
+                claims,
+            }
+        })
+        .collect();
+
+    HostnameReport { names }
+}
+
+/// Returns the earlier start, `None` being no start at all
+fn earliest(a: Option<Date>, b: Option<Date>) -> Option<Date> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        _ => None,
+    }
+}
+
+/// Returns the later end, `None` being no end at all
+fn latest(a: Option<Date>, b: Option<Date>) -> Option<Date> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        _ => None,
+    }
+}
+
+fn start(period: (Option<Date>, Option<Date>)) -> i64 {
+    period.0.map_or(i64::MIN, |d| d.timestamp())
+}
+
+fn end(period: (Option<Date>, Option<Date>)) -> i64 {
+    period.1.map_or(i64::MAX, |d| d.timestamp())
+}
+
+/// Merges overlapping or touching periods
+fn merge(mut periods: Vec<(Option<Date>, Option<Date>)>) -> Vec<(Option<Date>, Option<Date>)> {
+    periods.sort_by_key(|&p| (start(p), end(p)));
+
+    let mut res: Vec<(Option<Date>, Option<Date>)> = Vec::new();
+    for p in periods {
+        match res.last_mut() {
+            Some(last) if start(p) <= end(*last) => {
+                if end(p) > end(*last) {
+                    last.1 = p.1;
+                }
+            }
+            _ => res.push(p),
+        }
+    }
+
+    res
+}
@@ -0,0 +1,334 @@
+//! Detection of randomised hardware addresses and correlation of the
+//! addresses of one device.
+//!
+//! Many clients pick a random, locally administered, hardware address per
+//! network or per day, so counting addresses overcounts devices. Addresses
+//! are grouped into clusters that are likely one device:
+//!
+//! * addresses sending the same `uid` are linked with high confidence,
+//! * a randomised address and another address with the same client hostname
+//!   are linked if the other one was the last address with the name seen
+//!   before the first was, with higher confidence if it took over within
+//!   [`CorrelateOptions::handover`] after the other was last seen.
+//!
+//! Generic names like `android` or `iphone` are used by many devices, so a
+//! client hostname held by more than [`CorrelateOptions::max_concurrent`]
+//! addresses at the same time links no addresses at all.
+//!
+//! The confidence of a cluster is the one of the weakest link needed to
+//! connect all its addresses.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Duration;
+
+use crate::common::Date;
+use crate::leases::Leases;
+
+const UID_CONFIDENCE: f64 = 0.95;
+const HANDOVER_CONFIDENCE: f64 = 0.8;
+const HOSTNAME_CONFIDENCE: f64 = 0.6;
+
+/// Returns whether the hardware address is a locally administered unicast
+/// address, as randomised addresses are. Malformed addresses are not.
+pub fn is_randomised<S: AsRef<str>>(mac: S) -> bool {
+    let first = mac.as_ref().split(':').next().unwrap_or("");
+    match u8::from_str_radix(first, 16) {
+        Ok(octet) if first.len() <= 2 => octet & 0x02 != 0 && octet & 0x01 == 0,
+        _ => false,
+    }
+}
+
+/// Reason addresses were put in the same cluster
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Evidence {
+    Uid,
+    ClientHostname,
+    /// Same client hostname, and one address appeared shortly after the
+    /// other disappeared
+    Handover,
+}
+
+impl fmt::Display for Evidence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Evidence::Uid => write!(f, "uid"),
+            Evidence::ClientHostname => write!(f, "client-hostname"),
+            Evidence::Handover => write!(f, "handover"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CorrelateOptions {
+    /// Longest time between two addresses with the same client hostname for
+    /// the second to count as taking over from the first
+    pub handover: Duration,
+    /// Largest number of addresses seen with a client hostname at the same
+    /// time for the name to link addresses
+    pub max_concurrent: usize,
+}
+
+impl Default for CorrelateOptions {
+    fn default() -> CorrelateOptions {
+        CorrelateOptions {
+            handover: Duration::from_secs(3600),
+            max_concurrent: 1,
+        }
+    }
+}
+
+/// Hardware addresses believed to belong to one device
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeviceCluster {
+    /// Hardware addresses in lowercase, in ascending order
+    pub macs: Vec<String>,
+    /// Distinct uids, in ascending order
+    pub uids: Vec<String>,
+    /// Distinct client hostnames in lowercase, in ascending order
+    pub hostnames: Vec<String>,
+    /// Earliest `starts` of the declarations
+    pub first_seen: Option<Date>,
+    /// Latest `ends` of the declarations
+    pub last_seen: Option<Date>,
+    /// Kinds of links between the addresses, empty for a single address
+    pub evidence: Vec<Evidence>,
+    /// Between 0 and 1, 1 for a single address
+    pub confidence: f64,
+    /// Indices of the declarations
+    pub leases: Vec<usize>,
+}
+
+impl DeviceCluster {
+    /// Returns the randomised addresses of the cluster
+    pub fn randomised(&self) -> impl Iterator<Item = &str> {
+        self.macs
+            .iter()
+            .map(|m| m.as_str())
+            .filter(|m| is_randomised(m))
+    }
+}
+
+#[derive(Default)]
+struct Seen {
+    uids: Vec<String>,
+    hostnames: Vec<String>,
+    first: Option<Date>,
+    last: Option<Date>,
+    /// Bounds of the period seen, unbounded ends at the extremes
+    start: i64,
+    end: i64,
+    leases: Vec<usize>,
+}
+
+struct Link {
+    a: usize,
+    b: usize,
+    evidence: Evidence,
+    confidence: f64,
+}
+
+/// Groups the ethernet addresses of the leases into devices, ordered by their
+/// first address. Declarations without a hardware address are left out.
+pub fn correlate(leases: &Leases, options: &CorrelateOptions) -> Vec<DeviceCluster> {
+    let mut by_mac: BTreeMap<String, Seen> = BTreeMap::new();
+    for (i, l) in leases.iter().enumerate() {
+        let hw = match &l.hardware {
+            Some(hw) if hw.h_type == "ethernet" => hw,
+            _ => continue,
+        };
+
+        let seen = by_mac.entry(hw.mac.to_lowercase()).or_insert_with(|| Seen {
+            start: i64::MAX,
+            end: i64::MIN,
+            ..Seen::default()
+        });
+        push_distinct(&mut seen.uids, l.uid.clone());
+        push_distinct(
+            &mut seen.hostnames,
+            l.client_hostname.as_ref().map(|h| h.to_lowercase()),
+        );
+        seen.first = match (seen.first, l.dates.starts) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        seen.last = seen.last.max(l.dates.ends);
+        seen.start = seen
+            .start
+            .min(l.dates.starts.map_or(i64::MIN, |d| d.timestamp()));
+        seen.end = seen
+            .end
+            .max(l.dates.ends.map_or(i64::MAX, |d| d.timestamp()));
+        seen.leases.push(i);
+    }
+
+    let macs: Vec<(String, Seen)> = by_mac.into_iter().collect();
+    let mut links = links(&macs, options);
+    links.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+
+    // union-find, strongest links first, so the weakest link merging two
+    // clusters bounds the confidence
+    let mut parent: Vec<usize> = (0..macs.len()).collect();
+    let mut confidence = vec![1.0; macs.len()];
+    let mut evidence: Vec<Vec<Evidence>> = vec![Vec::new(); macs.len()];
+    for link in links {
+        let (a, b) = (root(&mut parent, link.a), root(&mut parent, link.b));
+        if a == b {
+            continue;
+        }
+        let (keep, merged) = (a.min(b), a.max(b));
+        parent[merged] = keep;
+        confidence[keep] = link
+            .confidence
+            .min(confidence[keep])
+            .min(confidence[merged]);
+        let moved = std::mem::take(&mut evidence[merged]);
+        evidence[keep].extend(moved);
+        evidence[keep].push(link.evidence);
+    }
+
+    let mut clusters: BTreeMap<usize, DeviceCluster> = BTreeMap::new();
+    for (n, (mac, seen)) in macs.into_iter().enumerate() {
+        let r = root(&mut parent, n);
+        let c = clusters.entry(r).or_insert_with(|| DeviceCluster {
+            macs: Vec::new(),
+            uids: Vec::new(),
+            hostnames: Vec::new(),
+            first_seen: None,
+            last_seen: None,
+            evidence: evidence[r].clone(),
+            confidence: confidence[r],
+            leases: Vec::new(),
+        });
+        c.macs.push(mac);
+        c.uids.extend(seen.uids);
+        c.hostnames.extend(seen.hostnames);
+        c.first_seen = match (c.first_seen, seen.first) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        c.last_seen = c.last_seen.max(seen.last);
+        c.leases.extend(seen.leases);
+    }
+
+    clusters
+        .into_values()
+        .map(|mut c| {
+            for v in [&mut c.uids, &mut c.hostnames].iter_mut() {
+                v.sort();
+                v.dedup();
+            }
+            c.evidence.sort();
+            c.evidence.dedup();
+            c.leases.sort_unstable();
+            c
+        })
+        .collect()
+}
+
+fn links(macs: &[(String, Seen)], options: &CorrelateOptions) -> Vec<Link> {
+    let mut by_uid: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    let mut by_hostname: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    for (n, (_, seen)) in macs.iter().enumerate() {
+        for uid in seen.uids.iter() {
+            by_uid.entry(uid).or_default().push(n);
+        }
+        for hn in seen.hostnames.iter() {
+            by_hostname.entry(hn).or_default().push(n);
+        }
+    }
+
+    let mut links = Vec::new();
+    for same in by_uid.values() {
+        for pair in same.windows(2) {
+            links.push(Link {
+                a: pair[0],
+                b: pair[1],
+                evidence: Evidence::Uid,
+                confidence: UID_CONFIDENCE,
+            });
+        }
+    }
+
+    let handover = options.handover.as_secs() as i64;
+    for same in by_hostname.values() {
+        if concurrent(macs, same) > options.max_concurrent {
+            continue;
+        }
+
+        for &b in same.iter() {
+            let after = &macs[b];
+            // the address last seen before this one, addresses seen at the
+            // same time are two devices with the same name
+            let closest = same
+                .iter()
+                .copied()
+                .filter(|&a| {
+                    let before = &macs[a];
+                    before.1.end < after.1.start
+                        && (is_randomised(&before.0) || is_randomised(&after.0))
+                })
+                .max_by_key(|&a| (macs[a].1.end, macs[a].1.start, a));
+            let a = match closest {
+                Some(a) => a,
+                None => continue,
+            };
+
+            let quick = after.1.start - macs[a].1.end <= handover;
+            links.push(Link {
+                a,
+                b,
+                evidence: if quick {
+                    Evidence::Handover
+                } else {
+                    Evidence::ClientHostname
+                },
+                confidence: if quick {
+                    HANDOVER_CONFIDENCE
+                } else {
+                    HOSTNAME_CONFIDENCE
+                },
+            });
+        }
+    }
+
+    links
+}
+
+/// Returns the largest number of the addresses seen at the same time, each
+/// one from its first `starts` up to, not including, its last `ends`
+fn concurrent(macs: &[(String, Seen)], same: &[usize]) -> usize {
+    let mut changes: Vec<(i64, isize)> = Vec::new();
+    for &n in same.iter() {
+        changes.push((macs[n].1.start, 1));
+        changes.push((macs[n].1.end, -1));
+    }
+    // at the same time, addresses leave before others arrive
+    changes.sort_unstable();
+
+    let mut count = 0;
+    let mut max = 0;
+    for (_, change) in changes {
+        count += change;
+        max = max.max(count);
+    }
+
+    max as usize
+}
+
+fn root(parent: &mut [usize], mut n: usize) -> usize {
+    while parent[n] != n {
+        parent[n] = parent[parent[n]];
+        n = parent[n];
+    }
+
+    n
+}
+
+fn push_distinct(values: &mut Vec<String>, value: Option<String>) {
+    if let Some(v) = value {
+        if !values.contains(&v) {
+            values.push(v);
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+
+use crate::common::Date;
+use crate::leases::Lease;
+use crate::leases::Leases;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Interval {
+    start: i64,
+    end: i64,
+    lease: usize,
+}
+
+impl Interval {
+    fn from_lease(i: usize, l: &Lease) -> Interval {
+        Interval {
+            start: l.dates.starts.map_or(i64::MIN, |d| d.timestamp()),
+            end: l.dates.ends.map_or(i64::MAX, |d| d.timestamp()),
+            lease: i,
+        }
+    }
+}
+
+/// Static interval tree stored as a balanced binary search tree over intervals
+/// sorted by their start. Each node also keeps the maximal end of its subtree,
+/// so subtrees that end before the queried window can be skipped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct IntervalTree {
+    intervals: Vec<Interval>,
+    max_end: Vec<i64>,
+}
+
+impl IntervalTree {
+    fn new(mut intervals: Vec<Interval>) -> IntervalTree {
+        intervals.sort_by_key(|i| (i.start, i.lease));
+        let mut tree = IntervalTree {
+            max_end: vec![i64::MIN; intervals.len()],
+            intervals,
+        };
+        tree.build(0, tree.intervals.len());
+
+        tree
+    }
+
+    fn build(&mut self, lo: usize, hi: usize) -> i64 {
+        if lo >= hi {
+            return i64::MIN;
+        }
+
+        let mid = lo + (hi - lo) / 2;
+        let max_end = self.intervals[mid]
+            .end
+            .max(self.build(lo, mid))
+            .max(self.build(mid + 1, hi));
+        self.max_end[mid] = max_end;
+
+        max_end
+    }
+
+    /// Collects leases of all intervals overlapping `[from, to]`, the end of an
+    /// interval being exclusive
+    fn overlapping(&self, from: i64, to: i64, res: &mut Vec<usize>) {
+        self.query(0, self.intervals.len(), from, to, res);
+    }
+
+    fn query(&self, lo: usize, hi: usize, from: i64, to: i64, res: &mut Vec<usize>) {
+        if lo >= hi {
+            return;
+        }
+
+        let mid = lo + (hi - lo) / 2;
+        if self.max_end[mid] <= from {
+            return;
+        }
+
+        self.query(lo, mid, from, to, res);
+
+        let i = &self.intervals[mid];
+        if i.start > to {
+            return;
+        }
+        if i.end > from {
+            res.push(i.lease);
+        }
+
+        self.query(mid + 1, hi, from, to, res);
+    }
+}
+
+/// Index over the lease time intervals for point-in-time and time window
+/// queries.
+///
+/// Lease intervals follow the [`Lease::is_active_at`] semantics: `starts` is
+/// inclusive, `ends` is exclusive and a missing date leaves the interval
+/// unbounded on that side. Abandoned leases are indexed as well. Hardware
+/// addresses are matched case-insensitively.
+///
+/// Queries return leases in the order they appear in the journal.
+#[derive(Clone, Debug)]
+pub struct LeaseIndex<'a> {
+    leases: Vec<&'a Lease>,
+    all: IntervalTree,
+    by_ip: HashMap<&'a str, IntervalTree>,
+    by_mac: HashMap<String, IntervalTree>,
+}
+
+impl<'a> LeaseIndex<'a> {
+    pub fn new(leases: &'a Leases) -> LeaseIndex<'a> {
+        let mut all = Vec::new();
+        let mut by_ip: HashMap<&'a str, Vec<Interval>> = HashMap::new();
+        let mut by_mac: HashMap<String, Vec<Interval>> = HashMap::new();
+
+        for (i, l) in leases.iter().enumerate() {
+            let interval = Interval::from_lease(i, l);
+
+            by_ip.entry(&l.ip).or_default().push(interval.clone());
+            if let Some(hw) = &l.hardware {
+                by_mac
+                    .entry(hw.mac.to_ascii_lowercase())
+                    .or_default()
+                    .push(interval.clone());
+            }
+            all.push(interval);
+        }
+
+        LeaseIndex {
+            leases: leases.iter().collect(),
+            all: IntervalTree::new(all),
+            by_ip: by_ip
+                .into_iter()
+                .map(|(k, is)| (k, IntervalTree::new(is)))
+                .collect(),
+            by_mac: by_mac
+                .into_iter()
+                .map(|(k, is)| (k, IntervalTree::new(is)))
+                .collect(),
+        }
+    }
+
+    /// Returns leases of `ip` that were active at `when`
+    pub fn ip_holders_at<S: AsRef<str>>(&self, ip: S, when: Date) -> Vec<&'a Lease> {
+        let ts = when.timestamp();
+        self.collect(self.by_ip.get(ip.as_ref()), ts, ts)
+    }
+
+    /// Returns leases of `mac` that were active at any moment of the window
+    /// `[from, to]`
+    pub fn mac_leases_during<S: AsRef<str>>(&self, mac: S, from: Date, to: Date) -> Vec<&'a Lease> {
+        let mac = mac.as_ref().to_ascii_lowercase();
+        self.collect(self.by_mac.get(&mac), from.timestamp(), to.timestamp())
+    }
+
+    /// Returns distinct IP addresses held by `mac` at any moment of the window
+    /// `[from, to]`
+    pub fn mac_ips_during<S: AsRef<str>>(&self, mac: S, from: Date, to: Date) -> Vec<&'a str> {
+        let mut res: Vec<&'a str> = Vec::new();
+        for l in self.mac_leases_during(mac, from, to) {
+            if !res.contains(&l.ip.as_str()) {
+                res.push(&l.ip);
+            }
+        }
+
+        res
+    }
+
+    /// Returns all leases that were active at `when`
+    pub fn active_at(&self, when: Date) -> Vec<&'a Lease> {
+        let ts = when.timestamp();
+        self.collect(Some(&self.all), ts, ts)
+    }
+
+    /// Returns all leases that were active at any moment of the window
+    /// `[from, to]`
+    pub fn overlapping(&self, from: Date, to: Date) -> Vec<&'a Lease> {
+        self.collect(Some(&self.all), from.timestamp(), to.timestamp())
+    }
+
+    fn collect(&self, tree: Option<&IntervalTree>, from: i64, to: i64) -> Vec<&'a Lease> {
+        let mut res = Vec::new();
+        if let Some(tree) = tree {
+            tree.overlapping(from, to, &mut res);
+        }
+        res.sort_unstable();
+
+        res.into_iter().map(|i| self.leases[i]).collect()
+    }
+}
@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::sync::Arc;
+
+use crate::common::Date;
+use crate::leases::{Hardware, Lease, LeaseDates, Leases, LeasesMethods};
+use crate::parser::{self, ParseError};
+
+/// Handle of a string stored in an [`Interner`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Symbol(u32);
+
+/// Stores every distinct string only once
+#[derive(Clone, Debug, Default)]
+pub struct Interner {
+    strings: Vec<Arc<str>>,
+    symbols: HashMap<Arc<str>, Symbol>,
+}
+
+impl Interner {
+    pub fn new() -> Interner {
+        Interner::default()
+    }
+
+    /// Returns the symbol of `s`, storing it first if needed. Panics if more
+    /// than `u32::MAX` distinct strings are stored.
+    pub fn intern<S: AsRef<str>>(&mut self, s: S) -> Symbol {
+        let s = s.as_ref();
+        if let Some(&sym) = self.symbols.get(s) {
+            return sym;
+        }
+
+        let sym = Symbol(u32::try_from(self.strings.len()).expect("too many strings interned"));
+        let s: Arc<str> = Arc::from(s);
+        self.strings.push(s.clone());
+        self.symbols.insert(s, sym);
+
+        sym
+    }
+
+    /// Returns the symbol of `s` if it has been interned
+    pub fn get<S: AsRef<str>>(&self, s: S) -> Option<Symbol> {
+        self.symbols.get(s.as_ref()).copied()
+    }
+
+    /// Returns the string of `sym`. Panics if `sym` belongs to another
+    /// interner.
+    pub fn resolve(&self, sym: Symbol) -> &str {
+        &self.strings[sym.0 as usize]
+    }
+
+    /// Returns the number of distinct strings
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct InternedLease {
+    ip: Symbol,
+    /// `starts` and `ends` as timestamps
+    starts: Option<i64>,
+    ends: Option<i64>,
+    hardware: Option<(Symbol, Symbol)>,
+    uid: Option<Symbol>,
+    client_hostname: Option<Symbol>,
+    hostname: Option<Symbol>,
+    abandoned: bool,
+}
+
+/// Leases with IP addresses, hardware addresses, uids and hostnames stored in
+/// a shared [`Interner`].
+///
+/// Journal-like leases files repeat these strings in every declaration of the
+/// same client, so sharing them takes considerably less memory than
+/// [`Leases`]. Dates are kept as timestamps, so weekdays are computed from the
+/// date. Leases are accessed through [`LeaseRef`].
+#[derive(Clone, Debug, Default)]
+pub struct InternedLeases {
+    interner: Interner,
+    leases: Vec<InternedLease>,
+}
+
+impl InternedLeases {
+    pub fn new() -> InternedLeases {
+        InternedLeases::default()
+    }
+
+    pub fn from_leases(leases: &Leases) -> InternedLeases {
+        let mut res = InternedLeases::new();
+        for l in leases.iter() {
+            res.push(l);
+        }
+
+        res
+    }
+
+    pub fn push(&mut self, l: &Lease) {
+        let i = &mut self.interner;
+        let lease = InternedLease {
+            ip: i.intern(&l.ip),
+            starts: l.dates.starts.map(|d| d.timestamp()),
+            ends: l.dates.ends.map(|d| d.timestamp()),
+            hardware: l
+                .hardware
+                .as_ref()
+                .map(|hw| (i.intern(&hw.h_type), i.intern(&hw.mac))),
+            uid: l.uid.as_ref().map(|s| i.intern(s)),
+            client_hostname: l.client_hostname.as_ref().map(|s| i.intern(s)),
+            hostname: l.hostname.as_ref().map(|s| i.intern(s)),
+            abandoned: l.abandoned,
+        };
+        self.leases.push(lease);
+    }
+
+    pub fn get(&self, i: usize) -> Option<LeaseRef<'_>> {
+        self.leases.get(i).map(|lease| LeaseRef {
+            interner: &self.interner,
+            lease,
+        })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = LeaseRef<'_>> {
+        self.leases.iter().map(move |lease| LeaseRef {
+            interner: &self.interner,
+            lease,
+        })
+    }
+
+    pub fn interner(&self) -> &Interner {
+        &self.interner
+    }
+
+    pub fn len(&self) -> usize {
+        self.leases.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.leases.is_empty()
+    }
+
+    pub fn to_leases(&self) -> Leases {
+        let mut res = Leases::new();
+        for l in self.iter() {
+            res.push(l.to_lease());
+        }
+
+        res
+    }
+}
+
+/// Parses the leases file, interning the strings of every declaration as soon
+/// as it is lexed and parsed rather than building [`Leases`] first
+pub fn parse_interned<S: Into<String>>(input: S) -> Result<InternedLeases, ParseError> {
+    let mut res = InternedLeases::new();
+    parser::parse_with(input, |l| res.push(&l))?;
+
+    Ok(res)
+}
+
+impl From<&Leases> for InternedLeases {
+    fn from(leases: &Leases) -> InternedLeases {
+        InternedLeases::from_leases(leases)
+    }
+}
+
+/// Lease borrowed from [`InternedLeases`]
+#[derive(Clone, Copy, Debug)]
+pub struct LeaseRef<'a> {
+    interner: &'a Interner,
+    lease: &'a InternedLease,
+}
+
+impl<'a> LeaseRef<'a> {
+    pub fn ip(&self) -> &'a str {
+        self.interner.resolve(self.lease.ip)
+    }
+
+    pub fn dates(&self) -> LeaseDates {
+        LeaseDates {
+            starts: self.lease.starts.map(Date::from_timestamp),
+            ends: self.lease.ends.map(Date::from_timestamp),
+        }
+    }
+
+    pub fn hardware_type(&self) -> Option<&'a str> {
+        self.lease.hardware.map(|(t, _)| self.interner.resolve(t))
+    }
+
+    pub fn mac(&self) -> Option<&'a str> {
+        self.lease
+            .hardware
+            .map(|(_, mac)| self.interner.resolve(mac))
+    }
+
+    pub fn uid(&self) -> Option<&'a str> {
+        self.lease.uid.map(|s| self.interner.resolve(s))
+    }
+
+    pub fn client_hostname(&self) -> Option<&'a str> {
+        self.lease.client_hostname.map(|s| self.interner.resolve(s))
+    }
+
+    pub fn hostname(&self) -> Option<&'a str> {
+        self.lease.hostname.map(|s| self.interner.resolve(s))
+    }
+
+    pub fn abandoned(&self) -> bool {
+        self.lease.abandoned
+    }
+
+    pub fn ip_symbol(&self) -> Symbol {
+        self.lease.ip
+    }
+
+    pub fn mac_symbol(&self) -> Option<Symbol> {
+        self.lease.hardware.map(|(_, mac)| mac)
+    }
+
+    /// See [`Lease::is_active_at`]
+    pub fn is_active_at(&self, when: Date) -> bool {
+        let when = when.timestamp();
+        self.lease.starts.is_none_or(|s| s <= when) && self.lease.ends.is_none_or(|e| e > when)
+    }
+
+    pub fn to_lease(&self) -> Lease {
+        Lease {
+            ip: self.ip().to_owned(),
+            dates: self.dates(),
+            hardware: self.lease.hardware.map(|(t, mac)| Hardware {
+                h_type: self.interner.resolve(t).to_owned(),
+                mac: self.interner.resolve(mac).to_owned(),
+            }),
+            uid: self.uid().map(|s| s.to_owned()),
+            client_hostname: self.client_hostname().map(|s| s.to_owned()),
+            hostname: self.hostname().map(|s| s.to_owned()),
+            abandoned: self.lease.abandoned,
+        }
+    }
+}
@@ -1,11 +1,18 @@
 use std::collections::HashSet;
+use std::fmt;
 use std::iter::Peekable;
+use std::net::IpAddr;
 use std::ops::Index;
 
 use crate::common::Date;
 use crate::lex::LexItem;
+use crate::net::Prefix;
+use crate::oui::OuiDb;
+use crate::table::LeaseTable;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 pub enum LeaseKeyword {
     Abandoned,
     ClientHostname,
@@ -17,6 +24,7 @@ pub enum LeaseKeyword {
 }
 
 impl LeaseKeyword {
+    #[allow(clippy::inherent_to_string, clippy::match_ref_pats)]
     pub fn to_string(&self) -> String {
         match self {
             &LeaseKeyword::Abandoned => "abandoned".to_owned(),
@@ -43,18 +51,60 @@ impl LeaseKeyword {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LeaseDates {
     pub starts: Option<Date>,
     pub ends: Option<Date>,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Hardware {
     pub h_type: String,
     pub mac: String,
 }
 
+/// Identity of a client, the client identifier if known and the hardware
+/// address otherwise, as dhcpd identifies clients. Hardware addresses and
+/// hexadecimal identifiers are lowercased, see [`Lease::client_id`]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ClientId {
+    MAC(String),
+    Uid(String),
+}
+
+impl fmt::Display for ClientId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClientId::MAC(mac) => write!(f, "{}", mac),
+            ClientId::Uid(uid) => write!(f, "uid {}", uid),
+        }
+    }
+}
+
+/// State of a lease at some point in time
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LeaseState {
+    Abandoned,
+    Active,
+    Expired,
+    /// The lease starts later
+    Future,
+}
+
+impl fmt::Display for LeaseState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            LeaseState::Abandoned => "abandoned",
+            LeaseState::Active => "active",
+            LeaseState::Expired => "expired",
+            LeaseState::Future => "future",
+        };
+        write!(f, "{}", s)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum LeasesField {
     ClientHostname,
@@ -64,6 +114,7 @@ pub enum LeasesField {
 }
 
 impl LeasesField {
+    #[allow(clippy::manual_map, clippy::type_complexity)]
     fn value_getter(&self) -> Box<dyn Fn(&Lease) -> Option<String>> {
         match &self {
             LeasesField::ClientHostname => {
@@ -81,7 +132,8 @@ impl LeasesField {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Leases(Vec<Lease>);
 
 impl Index<usize> for Leases {
@@ -92,6 +144,15 @@ impl Index<usize> for Leases {
     }
 }
 
+impl IntoIterator for Leases {
+    type Item = Lease;
+    type IntoIter = std::vec::IntoIter<Lease>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
 pub trait LeasesMethods {
     fn all(&self) -> Vec<Lease>;
 
@@ -127,6 +188,7 @@ pub trait LeasesMethods {
     #[deprecated(since = "0.4.3", note="any filtering logic should be done by user")]
     fn by_client_hostname_all<S: AsRef<str>>(&self, hostname: S) -> Vec<Lease>;
 
+    #[allow(clippy::new_ret_no_self)]
     fn new() -> Leases;
     fn push(&mut self, l: Lease);
     fn hostnames(&self) -> HashSet<String>;
@@ -169,6 +231,7 @@ impl LeasesMethods for Leases {
         None
     }
 
+    #[allow(clippy::manual_find)]
     fn by_leased<S: AsRef<str>>(&self, ip: S) -> Option<Lease> {
         let mut ls = self.0.clone();
         ls.reverse();
@@ -182,6 +245,7 @@ impl LeasesMethods for Leases {
         None
     }
 
+    #[allow(clippy::needless_return)]
     fn by_leased_all<S: AsRef<str>>(&self, ip: S) -> Vec<Lease> {
         let mut result = Vec::new();
         let ls = self.0.clone();
@@ -209,6 +273,7 @@ impl LeasesMethods for Leases {
         None
     }
 
+    #[allow(clippy::needless_return)]
     fn by_mac_all<S: AsRef<str>>(&self, mac: S) -> Vec<Lease> {
         let mut result = Vec::new();
         let ls = self.0.clone();
@@ -275,6 +340,7 @@ impl LeasesMethods for Leases {
         self.0.push(l);
     }
 
+    #[allow(clippy::needless_return, clippy::unnecessary_unwrap)]
     fn hostnames(&self) -> HashSet<String> {
         let mut res = HashSet::new();
         let ls = self.0.clone();
@@ -288,6 +354,7 @@ impl LeasesMethods for Leases {
         return res;
     }
 
+    #[allow(clippy::needless_return, clippy::unnecessary_unwrap)]
     fn client_hostnames(&self) -> HashSet<String> {
         let mut res = HashSet::new();
         let ls = self.0.clone();
@@ -302,7 +369,67 @@ impl LeasesMethods for Leases {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+impl Leases {
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Lease> {
+        self.0.iter()
+    }
+
+    /// Returns the current state of the leases journal, see [`LeaseTable`].
+    pub fn current(&self) -> LeaseTable {
+        LeaseTable::from_leases(self)
+    }
+
+    /// Returns the leases of addresses in any of the networks
+    pub fn in_subnets(&self, prefixes: &[Prefix]) -> Leases {
+        let mut res = Leases::new();
+        for l in self.0.iter() {
+            if let Ok(ip) = l.ip.parse::<IpAddr>() {
+                if prefixes.iter().any(|p| p.contains(&ip)) {
+                    res.push(l.clone());
+                }
+            }
+        }
+
+        res
+    }
+
+    /// Returns the leases of every network, in the order of `prefixes`. Leases
+    /// belong to the longest of the networks containing their address and
+    /// leases outside of all networks are left out.
+    pub fn group_by_subnet(&self, prefixes: &[Prefix]) -> Vec<(Prefix, Leases)> {
+        let mut res: Vec<(Prefix, Leases)> = prefixes.iter().map(|p| (*p, Leases::new())).collect();
+
+        for l in self.0.iter() {
+            let ip = match l.ip.parse::<IpAddr>() {
+                Ok(ip) => ip,
+                Err(_) => continue,
+            };
+
+            // the first of the longest ones
+            let best = prefixes
+                .iter()
+                .enumerate()
+                .filter(|(_, p)| p.contains(&ip))
+                .min_by_key(|(n, p)| (std::cmp::Reverse(p.prefix_len()), *n));
+            if let Some((n, _)) = best {
+                res[n].1.push(l.clone());
+            }
+        }
+
+        res
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Lease {
     pub ip: String,
     pub dates: LeaseDates,
@@ -329,12 +456,44 @@ impl Lease {
         }
     }
 
+    /// Returns the key identifying the client holding the lease. Leases are
+    /// held by the same client if and only if their keys are equal.
+    pub fn client_id(&self) -> Option<ClientId> {
+        match (&self.uid, &self.hardware) {
+            (Some(uid), _) if uid.starts_with('"') => Some(ClientId::Uid(uid.clone())),
+            (Some(uid), _) => Some(ClientId::Uid(uid.to_ascii_lowercase())),
+            (None, Some(hw)) => Some(ClientId::MAC(hw.mac.to_ascii_lowercase())),
+            (None, None) => None,
+        }
+    }
+
+    /// Returns the manufacturer of the hardware address, see [`OuiDb::lookup`]
+    pub fn vendor<'a>(&self, db: &'a OuiDb) -> Option<&'a str> {
+        db.lookup(&self.hardware.as_ref()?.mac)
+    }
+
+    /// Returns the state at `when`. A lease is held from its `starts` up to,
+    /// not including, its `ends`.
+    pub fn state_at(&self, when: Date) -> LeaseState {
+        if self.abandoned {
+            LeaseState::Abandoned
+        } else if self.dates.starts.is_some_and(|s| s > when) {
+            LeaseState::Future
+        } else if self.dates.ends.is_some_and(|e| e <= when) {
+            LeaseState::Expired
+        } else {
+            LeaseState::Active
+        }
+    }
+
+    /// Returns whether the lease is held at `when`, see [`Lease::state_at`]
+    #[allow(clippy::needless_return)]
     pub fn is_active_at(&self, when: Date) -> bool {
         if self.dates.starts.is_some() && self.dates.starts.unwrap() > when {
             return false;
         }
 
-        if self.dates.ends.is_some() && self.dates.ends.unwrap() < when {
+        if self.dates.ends.is_some() && self.dates.ends.unwrap() <= when {
             return false;
         }
 
@@ -342,6 +501,42 @@ impl Lease {
     }
 }
 
+impl Default for Lease {
+    fn default() -> Lease {
+        Lease::new()
+    }
+}
+
+/// Formats the lease as a declaration of the `dhcpd.leases` file
+impl fmt::Display for Lease {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "lease {} {{", self.ip)?;
+        if let Some(starts) = self.dates.starts {
+            writeln!(f, "\tstarts {};", starts.to_leases_format())?;
+        }
+        if let Some(ends) = self.dates.ends {
+            writeln!(f, "\tends {};", ends.to_leases_format())?;
+        }
+        if let Some(hw) = &self.hardware {
+            writeln!(f, "\thardware {} {};", hw.h_type, hw.mac)?;
+        }
+        if let Some(uid) = &self.uid {
+            writeln!(f, "\tuid {};", uid)?;
+        }
+        if let Some(hn) = &self.client_hostname {
+            writeln!(f, "\tclient-hostname \"{}\";", hn)?;
+        }
+        if let Some(hn) = &self.hostname {
+            writeln!(f, "\thostname \"{}\";", hn)?;
+        }
+        if self.abandoned {
+            writeln!(f, "\tabandoned;")?;
+        }
+        write!(f, "}}")
+    }
+}
+
+#[allow(clippy::redundant_field_names, clippy::to_string_in_format_args)]
 pub fn parse_lease<'l, T: Iterator<Item = &'l LexItem>>(
     lease: &mut Lease,
     iter: &mut Peekable<T>,
@@ -352,27 +547,27 @@ pub fn parse_lease<'l, T: Iterator<Item = &'l LexItem>>(
                 iter.next();
                 let weekday = iter
                     .peek()
-                    .expect("Weekday for start date expected")
+                    .ok_or("Weekday for start date expected")?
                     .to_string();
                 iter.next();
                 let date = iter
                     .peek()
-                    .expect("Date for start date expected")
+                    .ok_or("Date for start date expected")?
                     .to_string();
                 iter.next();
                 let time = iter
                     .peek()
-                    .expect("Time for start date expected")
+                    .ok_or("Time for start date expected")?
                     .to_string();
                 iter.next();
 
                 let tz = iter
                     .peek()
-                    .expect("Timezone or semicolon expected")
+                    .ok_or("Timezone or semicolon expected")?
                     .to_string();
                 if tz != LexItem::Endl.to_string() {
                     iter.next();
-                    match iter.peek().expect("Semicolon expected") {
+                    match iter.peek().ok_or("Semicolon expected")? {
                         LexItem::Endl => (),
                         s => return Err(format!("Expected semicolon, found {}", s.to_string())),
                     }
@@ -384,21 +579,21 @@ pub fn parse_lease<'l, T: Iterator<Item = &'l LexItem>>(
                 iter.next();
                 let weekday = iter
                     .peek()
-                    .expect("Weekday for end date expected")
+                    .ok_or("Weekday for end date expected")?
                     .to_string();
                 iter.next();
-                let date = iter.peek().expect("Date for end date expected").to_string();
+                let date = iter.peek().ok_or("Date for end date expected")?.to_string();
                 iter.next();
-                let time = iter.peek().expect("Time for end date expected").to_string();
+                let time = iter.peek().ok_or("Time for end date expected")?.to_string();
                 iter.next();
                 let tz = iter
                     .peek()
-                    .expect("Timezone or semicolon expected")
+                    .ok_or("Timezone or semicolon expected")?
                     .to_string();
 
                 if tz != LexItem::Endl.to_string() {
                     iter.next();
-                    match iter.peek().expect("Semicolon expected") {
+                    match iter.peek().ok_or("Semicolon expected")? {
                         LexItem::Endl => (),
                         s => return Err(format!("Expected semicolon, found {}", s.to_string())),
                     }
@@ -408,11 +603,11 @@ pub fn parse_lease<'l, T: Iterator<Item = &'l LexItem>>(
             }
             LexItem::Opt(LeaseKeyword::Hardware) => {
                 iter.next();
-                let h_type = iter.peek().expect("Hardware type expected").to_string();
+                let h_type = iter.peek().ok_or("Hardware type expected")?.to_string();
                 iter.next();
-                let mac = iter.peek().expect("MAC address expected").to_string();
+                let mac = iter.peek().ok_or("MAC address expected")?.to_string();
                 iter.next();
-                match iter.peek().expect("Semicolon expected") {
+                match iter.peek().ok_or("Semicolon expected")? {
                     LexItem::Endl => (),
                     s => return Err(format!("Expected semicolon, found {}", s.to_string())),
                 }
@@ -426,10 +621,10 @@ pub fn parse_lease<'l, T: Iterator<Item = &'l LexItem>>(
                 iter.next();
                 lease
                     .uid
-                    .replace(iter.peek().expect("Client identifier expected").to_string());
+                    .replace(iter.peek().ok_or("Client identifier expected")?.to_string());
 
                 iter.next();
-                match iter.peek().expect("Semicolon expected") {
+                match iter.peek().ok_or("Semicolon expected")? {
                     LexItem::Endl => (),
                     s => return Err(format!("Expected semicolon, found {}", s.to_string())),
                 }
@@ -437,11 +632,11 @@ pub fn parse_lease<'l, T: Iterator<Item = &'l LexItem>>(
             LexItem::Opt(LeaseKeyword::ClientHostname) => {
                 iter.next();
                 lease.client_hostname.replace(unquote_hostname(
-                    iter.peek().expect("Client hostname expected").to_string(),
+                    iter.peek().ok_or("Client hostname expected")?.to_string(),
                 ));
 
                 iter.next();
-                match iter.peek().expect("Semicolon expected") {
+                match iter.peek().ok_or("Semicolon expected")? {
                     LexItem::Endl => (),
                     s => return Err(format!("Expected semicolon, found {}", s.to_string())),
                 }
@@ -449,11 +644,11 @@ pub fn parse_lease<'l, T: Iterator<Item = &'l LexItem>>(
             LexItem::Opt(LeaseKeyword::Hostname) => {
                 iter.next();
                 lease.hostname.replace(unquote_hostname(
-                    iter.peek().expect("Hostname expected").to_string(),
+                    iter.peek().ok_or("Hostname expected")?.to_string(),
                 ));
 
                 iter.next();
-                match iter.peek().expect("Semicolon expected") {
+                match iter.peek().ok_or("Semicolon expected")? {
                     LexItem::Endl => (),
                     s => return Err(format!("Expected semicolon, found {}", s.to_string())),
                 }
@@ -461,7 +656,7 @@ pub fn parse_lease<'l, T: Iterator<Item = &'l LexItem>>(
             LexItem::Opt(LeaseKeyword::Abandoned) => {
                 lease.abandoned = true;
                 iter.next();
-                match iter.peek().expect("Semicolon expected") {
+                match iter.peek().ok_or("Semicolon expected")? {
                     LexItem::Endl => (),
                     s => return Err(format!("Expected semicolon, found {}", s.to_string())),
                 }
@@ -29,41 +29,62 @@ pub fn lex<S>(input: S) -> Result<Vec<LexItem>, String>
 where
     S: Into<String>,
 {
-    let mut result = Vec::new();
-
     let input_str = input.into();
 
-    let mut it = input_str.chars().peekable();
-    while let Some(&c) = it.peek() {
-        match c {
-            '(' | ')' | '[' | ']' | '{' | '}' => {
-                result.push(LexItem::Paren(c));
-                it.next();
-            }
-            ' ' | '\n' | '\t' => {
-                it.next();
-            }
-            ';' => {
-                result.push(LexItem::Endl);
-                it.next();
-            }
-            _ => {
-                let w = get_word(&mut it);
-                let kw = ConfigKeyword::from(&w);
-                if kw.is_ok() {
-                    result.push(LexItem::Decl(kw.unwrap()));
-                } else {
-                    let kw = LeaseKeyword::from(&w);
+    Ok(Lexer::new(input_str.chars()).collect())
+}
+
+/// Lexer yielding the items one by one, so that the input can be parsed
+/// without holding the items of all of it
+pub struct Lexer<I: Iterator<Item = char>> {
+    it: Peekable<I>,
+}
+
+impl<I: Iterator<Item = char>> Lexer<I> {
+    pub fn new(chars: I) -> Lexer<I> {
+        Lexer {
+            it: chars.peekable(),
+        }
+    }
+}
+
+impl<I: Iterator<Item = char>> Iterator for Lexer<I> {
+    type Item = LexItem;
+
+    #[allow(clippy::unnecessary_unwrap)]
+    fn next(&mut self) -> Option<LexItem> {
+        let it = &mut self.it;
+        while let Some(&c) = it.peek() {
+            match c {
+                '(' | ')' | '[' | ']' | '{' | '}' => {
+                    it.next();
+                    return Some(LexItem::Paren(c));
+                }
+                c if c.is_whitespace() => {
+                    it.next();
+                }
+                ';' => {
+                    it.next();
+                    return Some(LexItem::Endl);
+                }
+                _ => {
+                    let w = get_word(it);
+                    let kw = ConfigKeyword::from(&w);
                     if kw.is_ok() {
-                        result.push(LexItem::Opt(kw.unwrap()));
+                        return Some(LexItem::Decl(kw.unwrap()));
                     } else {
-                        result.push(LexItem::Word(w));
+                        let kw = LeaseKeyword::from(&w);
+                        if kw.is_ok() {
+                            return Some(LexItem::Opt(kw.unwrap()));
+                        } else {
+                            return Some(LexItem::Word(w));
+                        }
                     }
                 }
             }
         }
+        None
     }
-    Ok(result)
 }
 
 fn get_word<T: Iterator<Item = char>>(iter: &mut Peekable<T>) -> String {
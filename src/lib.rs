@@ -1,5 +1,32 @@
+#[cfg(feature = "tokio")]
+pub mod async_io;
 pub mod common;
+pub mod compact;
+pub mod conflicts;
+pub mod decompress;
+pub mod events;
+pub mod export;
+pub mod follow;
+pub mod forecast;
+pub mod history;
+pub mod hostnames;
+pub mod identity;
+pub mod index;
+pub mod intern;
 pub mod leases;
+pub mod net;
+pub mod oui;
+#[cfg(feature = "rayon")]
+pub mod parallel;
 pub mod parser;
+pub mod query;
+pub mod reader;
+pub mod sessions;
+pub mod snapshot;
+pub mod stats;
+pub mod store;
+pub mod table;
+pub mod validate;
 
 mod lex;
+mod pattern;
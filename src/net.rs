@@ -0,0 +1,250 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::net::IpAddr;
+
+/// IPv4 or IPv6 network in CIDR notation, e.g. `192.168.0.0/24`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Prefix {
+    addr: IpAddr,
+    len: u8,
+}
+
+impl Prefix {
+    /// Parses `address/length`. An address without length is a network of a
+    /// single host. Host bits of the address are cleared.
+    pub fn from(s: &str) -> Result<Prefix, String> {
+        let (addr, len) = match s.split_once('/') {
+            Some((a, l)) => (a, Some(l)),
+            None => (s, None),
+        };
+
+        let addr: IpAddr = addr
+            .parse()
+            .map_err(|_| format!("'{}' is not an IP address", addr))?;
+        let max = max_len(&addr);
+        let len = match len {
+            Some(l) => l.parse::<u8>().ok().filter(|l| *l <= max).ok_or(format!(
+                "Prefix length should be a number between 0 and {}. {} is not",
+                max, l
+            ))?,
+            None => max,
+        };
+
+        Ok(Prefix {
+            addr: mask(addr, len),
+            len,
+        })
+    }
+
+    /// Returns the network address
+    pub fn addr(&self) -> IpAddr {
+        self.addr
+    }
+
+    pub fn prefix_len(&self) -> u8 {
+        self.len
+    }
+
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        self.addr.is_ipv4() == ip.is_ipv4() && mask(*ip, self.len) == self.addr
+    }
+
+    /// Like [`Prefix::contains`], but with the address as a string. Strings
+    /// which are not addresses are never contained.
+    pub fn contains_str<S: AsRef<str>>(&self, ip: S) -> bool {
+        match ip.as_ref().parse::<IpAddr>() {
+            Ok(ip) => self.contains(&ip),
+            Err(_) => false,
+        }
+    }
+
+    /// Returns the number of addresses, saturating at `u128::MAX`
+    pub fn size(&self) -> u128 {
+        self.range().size()
+    }
+
+    /// Returns the number of addresses usable by hosts, which are all but the
+    /// network and broadcast address in IPv4 networks shorter than /31
+    pub fn host_count(&self) -> u128 {
+        match self.addr {
+            IpAddr::V4(_) if self.len < 31 => self.size() - 2,
+            _ => self.size(),
+        }
+    }
+
+    /// Iterates over the addresses usable by hosts, see [`Prefix::host_count`]
+    pub fn hosts(&self) -> Addresses {
+        let range = self.range();
+        match self.addr {
+            IpAddr::V4(_) if self.len < 31 => Addresses {
+                next: Some(bits(range.first) + 1),
+                last: bits(range.last) - 1,
+                family: self.addr,
+            },
+            _ => range.iter(),
+        }
+    }
+
+    /// Returns all addresses of the network, network and broadcast address
+    /// included
+    pub fn range(&self) -> AddressRange {
+        let host_bits = u32::from(max_len(&self.addr) - self.len);
+        let last = bits(self.addr) | u128::MAX.checked_shr(128 - host_bits).unwrap_or(0);
+        AddressRange {
+            first: self.addr,
+            last: from_bits(&self.addr, last),
+        }
+    }
+}
+
+impl fmt::Display for Prefix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.len)
+    }
+}
+
+/// Inclusive range of IPv4 or IPv6 addresses, like a `range` statement of
+/// dhcpd, e.g. `10.0.0.10-10.0.0.200`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct AddressRange {
+    first: IpAddr,
+    last: IpAddr,
+}
+
+impl AddressRange {
+    pub fn new(first: IpAddr, last: IpAddr) -> Result<AddressRange, String> {
+        if first.is_ipv4() != last.is_ipv4() {
+            return Err(format!("{} and {} are of different families", first, last));
+        }
+        if bits(first) > bits(last) {
+            return Err(format!("{} is after {}", first, last));
+        }
+
+        Ok(AddressRange { first, last })
+    }
+
+    /// Parses `first-last`, a network in CIDR notation or a single address
+    pub fn from(s: &str) -> Result<AddressRange, String> {
+        let (first, last) = match s.split_once('-') {
+            Some(range) => range,
+            None => return Ok(Prefix::from(s)?.range()),
+        };
+
+        let parse = |a: &str| {
+            a.trim()
+                .parse::<IpAddr>()
+                .map_err(|_| format!("'{}' is not an IP address", a.trim()))
+        };
+        AddressRange::new(parse(first)?, parse(last)?)
+    }
+
+    pub fn first(&self) -> IpAddr {
+        self.first
+    }
+
+    pub fn last(&self) -> IpAddr {
+        self.last
+    }
+
+    /// Returns the number of addresses, saturating at `u128::MAX`
+    pub fn size(&self) -> u128 {
+        (bits(self.last) - bits(self.first)).saturating_add(1)
+    }
+
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        self.first.is_ipv4() == ip.is_ipv4()
+            && bits(self.first) <= bits(*ip)
+            && bits(*ip) <= bits(self.last)
+    }
+
+    /// Like [`AddressRange::contains`], but with the address as a string
+    pub fn contains_str<S: AsRef<str>>(&self, ip: S) -> bool {
+        match ip.as_ref().parse::<IpAddr>() {
+            Ok(ip) => self.contains(&ip),
+            Err(_) => false,
+        }
+    }
+
+    /// Iterates over the addresses in ascending order
+    pub fn iter(&self) -> Addresses {
+        Addresses {
+            next: Some(bits(self.first)),
+            last: bits(self.last),
+            family: self.first,
+        }
+    }
+}
+
+/// Iterator over consecutive addresses, see [`AddressRange::iter`] and
+/// [`Prefix::hosts`]
+#[derive(Clone, Debug)]
+pub struct Addresses {
+    /// `None` once exhausted
+    next: Option<u128>,
+    last: u128,
+    family: IpAddr,
+}
+
+impl Iterator for Addresses {
+    type Item = IpAddr;
+
+    fn next(&mut self) -> Option<IpAddr> {
+        let next = self.next.filter(|n| *n <= self.last)?;
+        self.next = next.checked_add(1);
+
+        Some(from_bits(&self.family, next))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = match self.next {
+            Some(n) if n <= self.last => (self.last - n).checked_add(1),
+            _ => Some(0),
+        };
+
+        match left.and_then(|n| usize::try_from(n).ok()) {
+            Some(n) => (n, Some(n)),
+            None => (usize::MAX, None),
+        }
+    }
+}
+
+impl fmt::Display for AddressRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{}", self.first, self.last)
+    }
+}
+
+fn bits(addr: IpAddr) -> u128 {
+    match addr {
+        IpAddr::V4(a) => u128::from(u32::from(a)),
+        IpAddr::V6(a) => u128::from(a),
+    }
+}
+
+/// Returns the address of the family of `family` with the given bits
+fn from_bits(family: &IpAddr, bits: u128) -> IpAddr {
+    match family {
+        IpAddr::V4(_) => IpAddr::V4((bits as u32).into()),
+        IpAddr::V6(_) => IpAddr::V6(bits.into()),
+    }
+}
+
+fn max_len(addr: &IpAddr) -> u8 {
+    match addr {
+        IpAddr::V4(_) => 32,
+        IpAddr::V6(_) => 128,
+    }
+}
+
+fn mask(addr: IpAddr, len: u8) -> IpAddr {
+    match addr {
+        IpAddr::V4(a) => {
+            let bits = u32::from(a) & u32::MAX.checked_shl(32 - u32::from(len)).unwrap_or(0);
+            IpAddr::V4(bits.into())
+        }
+        IpAddr::V6(a) => {
+            let bits = u128::from(a) & u128::MAX.checked_shl(128 - u32::from(len)).unwrap_or(0);
+            IpAddr::V6(bits.into())
+        }
+    }
+}
@@ -0,0 +1,194 @@
+//! Resolution of hardware addresses to manufacturers with the IEEE registry.
+//!
+//! [`OuiDb::load`] reads the `oui.txt` or `oui.csv` files published by the
+//! IEEE, including the registries of 28 and 36 bit assignments, which are
+//! preferred over 24 bit ones. With the `oui-snapshot` feature enabled,
+//! [`OuiDb::snapshot`] returns a small built-in registry of common vendors.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Registry of assignments, see the [module](self) documentation
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OuiDb {
+    /// Organization names by assignment in uppercase hexadecimal digits
+    entries: HashMap<String, String>,
+}
+
+impl OuiDb {
+    pub fn new() -> OuiDb {
+        OuiDb::default()
+    }
+
+    /// Parses a registry in the `oui.txt` or `oui.csv` format
+    pub fn from(input: &str) -> Result<OuiDb, String> {
+        let mut db = OuiDb::new();
+        if input.contains("(hex)") || input.contains("(base 16)") {
+            db.extend_txt(input)?;
+        } else {
+            db.extend_csv(input)?;
+        }
+
+        Ok(db)
+    }
+
+    /// Reads a registry file, see [`OuiDb::from`]
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<OuiDb, String> {
+        let path = path.as_ref();
+        let input = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+        OuiDb::from(&input).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Returns the built-in registry of common vendors
+    #[cfg(feature = "oui-snapshot")]
+    pub fn snapshot() -> OuiDb {
+        OuiDb::from(include_str!("../data/oui.csv")).expect("built-in registry is valid")
+    }
+
+    /// Adds an assignment of 6, 7 or 9 hexadecimal digits, separators
+    /// allowed, replacing an existing one
+    pub fn insert<S: Into<String>>(&mut self, assignment: &str, name: S) -> Result<(), String> {
+        let key = hex_digits(assignment);
+        if !matches!(key.len(), 6 | 7 | 9) || key.len() != significant_len(assignment) {
+            return Err(format!("'{}' is not an OUI assignment", assignment));
+        }
+
+        self.entries.insert(key, name.into());
+        Ok(())
+    }
+
+    /// Returns the organization the hardware address is assigned to, the
+    /// longest assignment matching
+    pub fn lookup<S: AsRef<str>>(&self, mac: S) -> Option<&str> {
+        let digits = hex_digits(mac.as_ref());
+        [9, 7, 6]
+            .iter()
+            .filter_map(|&n| digits.get(..n))
+            .find_map(|prefix| self.entries.get(prefix))
+            .map(|name| name.as_str())
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Reads lines like `00-00-0C   (hex)    Cisco Systems, Inc`. In the
+    /// registries of 28 and 36 bit assignments the `(hex)` line holds the 24
+    /// bit block and the following `(base 16)` line the range assigned in it,
+    /// like `000000-0FFFFF` or `70B3D5000000-70B3D5000FFF`.
+    fn extend_txt(&mut self, input: &str) -> Result<(), String> {
+        // line, 24 bit block and organization of the last `(hex)` line
+        let mut pending: Option<(usize, &str, &str)> = None;
+        for (n, line) in input.lines().enumerate() {
+            if let Some((assignment, name)) = line.split_once("(hex)") {
+                if let Some((m, block, name)) = pending.take() {
+                    self.insert(block, name)
+                        .map_err(|e| format!("line {}: {}", m + 1, e))?;
+                }
+                pending = Some((n, assignment.trim(), name.trim()));
+            } else if let Some((range, _)) = line.split_once("(base 16)") {
+                if let (Some((start, end)), Some((_, block, name))) =
+                    (range.trim().split_once('-'), pending)
+                {
+                    range_assignment(block, start, end)
+                        .and_then(|key| self.insert(&key, name))
+                        .map_err(|e| format!("line {}: {}", n + 1, e))?;
+                    pending = None;
+                }
+            }
+        }
+        if let Some((m, block, name)) = pending {
+            self.insert(block, name)
+                .map_err(|e| format!("line {}: {}", m + 1, e))?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads records of `Registry,Assignment,Organization Name,...`
+    fn extend_csv(&mut self, input: &str) -> Result<(), String> {
+        for (n, line) in input.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let fields = csv_fields(line);
+            if fields.first().map(|f| f.as_str()) == Some("Registry") {
+                continue;
+            }
+            match (fields.get(1), fields.get(2)) {
+                (Some(assignment), Some(name)) => self
+                    .insert(assignment, name.trim())
+                    .map_err(|e| format!("line {}: {}", n + 1, e))?,
+                _ => return Err(format!("line {}: expected at least 3 fields", n + 1)),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn hex_digits(s: &str) -> String {
+    s.chars()
+        .filter(|c| c.is_ascii_hexdigit())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+/// Returns the assignment of a range of addresses in a 24 bit block, the
+/// range given either within the block or as full addresses
+fn range_assignment(block: &str, start: &str, end: &str) -> Result<String, String> {
+    let (block, start, end) = (hex_digits(block), hex_digits(start), hex_digits(end));
+    let (start, end) = match (block.len(), start.len(), end.len()) {
+        (6, 6, 6) => (block.clone() + &start, block + &end),
+        (6, 12, 12) if start.starts_with(&block) => (start, end),
+        _ => return Err(format!("'{}-{}' is not an address range", start, end)),
+    };
+
+    let first = u64::from_str_radix(&start, 16).map_err(|e| e.to_string())?;
+    let last = u64::from_str_radix(&end, 16).map_err(|e| e.to_string())?;
+    let digits = match last.checked_sub(first) {
+        Some(0xFF_FFFF) => 6,
+        Some(0xF_FFFF) => 7,
+        Some(0xFFF) => 9,
+        _ => return Err(format!("'{}-{}' is not an assigned range", start, end)),
+    };
+    if start[digits..].chars().any(|c| c != '0') {
+        return Err(format!("'{}-{}' is not an assigned range", start, end));
+    }
+
+    Ok(start[..digits].to_owned())
+}
+
+/// Returns the number of characters other than separators
+fn significant_len(s: &str) -> usize {
+    s.chars().filter(|c| !matches!(c, ':' | '-' | '.')).count()
+}
+
+/// Splits a CSV record, fields may be quoted with `"` and quotes doubled
+fn csv_fields(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+
+    fields
+}
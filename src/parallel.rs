@@ -0,0 +1,110 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::str;
+
+use memmap2::Mmap;
+use rayon::prelude::*;
+
+use crate::decompress::{self, Compression};
+use crate::leases::Leases;
+use crate::leases::LeasesMethods;
+use crate::parser;
+use crate::parser::ParseError;
+use crate::parser::ParserResult;
+
+/// Chunks smaller than this are not worth a separate task
+const MIN_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Parses the input on all rayon threads.
+///
+/// The input is split into chunks at `lease` declarations starting on a new
+/// line, as dhcpd writes them. Leases keep the order of the input.
+pub fn parse_parallel<S: AsRef<[u8]>>(input: S) -> Result<ParserResult, ParseError> {
+    let input = input.as_ref();
+    let chunks = split_chunks(input, rayon::current_num_threads() * 4);
+
+    let parsed: Vec<Result<ParserResult, ParseError>> = chunks
+        .into_par_iter()
+        .map(|chunk| {
+            let text =
+                str::from_utf8(chunk).map_err(|e| format!("Input is not valid UTF-8: {}", e))?;
+            parser::parse(text)
+        })
+        .collect();
+
+    let mut leases = Leases::new();
+    for res in parsed {
+        for l in res?.leases {
+            leases.push(l);
+        }
+    }
+
+    Ok(ParserResult { leases })
+}
+
+/// Memory-maps the file at `path` and parses it with [`parse_parallel`].
+/// Compressed files are decompressed into memory instead.
+///
+/// The file must not be truncated while it is being parsed, so this is meant
+/// for rotated archives rather than the live leases file.
+pub fn parse_path_parallel<P: AsRef<Path>>(path: P) -> Result<ParserResult, ParseError> {
+    let path = path.as_ref();
+    let file = File::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    // Safety: the mapping is only read, and the documentation asks callers not
+    // to modify the file while it is mapped
+    let map = unsafe { Mmap::map(&file) }.map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    // compressed archives have to be inflated into memory first
+    if Compression::detect(&map) != Compression::None {
+        let mut input = Vec::new();
+        decompress::decompress(&map[..])
+            .and_then(|mut r| r.read_to_end(&mut input).map_err(|e| e.to_string()))
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+
+        return parse_parallel(input);
+    }
+
+    parse_parallel(&map[..])
+}
+
+/// Splits input into roughly `count` chunks, each of them starting at a
+/// `lease` declaration
+fn split_chunks(input: &[u8], count: usize) -> Vec<&[u8]> {
+    let size = (input.len() / count.max(1)).max(MIN_CHUNK_SIZE);
+
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < input.len() {
+        let end = match next_declaration(input, start + size) {
+            Some(end) => end,
+            None => input.len(),
+        };
+        chunks.push(&input[start..end]);
+        start = end;
+    }
+
+    chunks
+}
+
+/// Finds the first line at or after `from` which starts a `lease` declaration
+fn next_declaration(input: &[u8], from: usize) -> Option<usize> {
+    let mut i = from;
+    while i < input.len() {
+        let nl = i + input[i..].iter().position(|&c| c == b'\n')?;
+        let line = &input[nl + 1..];
+        let indent = line
+            .iter()
+            .take_while(|&&c| c == b' ' || c == b'\t')
+            .count();
+        let line = &line[indent..];
+
+        if line.starts_with(b"lease") && line.get(5).is_some_and(|c| c.is_ascii_whitespace()) {
+            return Some(nl + 1);
+        }
+        i = nl + 1;
+    }
+
+    None
+}
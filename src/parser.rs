@@ -1,11 +1,20 @@
+use std::io::Read;
+use std::path::Path;
+
+use crate::decompress;
 use crate::leases::parse_lease;
 use crate::leases::Lease;
 use crate::leases::Leases;
 pub use crate::leases::LeasesMethods;
 use crate::lex::lex;
 use crate::lex::LexItem;
+use crate::lex::Lexer;
+
+/// Error returned when the input is not a valid leases file
+pub type ParseError = String;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ParserResult {
     pub leases: Leases,
 }
@@ -16,6 +25,7 @@ pub enum ConfigKeyword {
 }
 
 impl ConfigKeyword {
+    #[allow(clippy::inherent_to_string)]
     pub fn to_string(&self) -> String {
         match self {
             &ConfigKeyword::Lease => "lease".to_owned(),
@@ -30,8 +40,17 @@ impl ConfigKeyword {
     }
 }
 
+#[allow(clippy::redundant_field_names)]
 fn parse_config(tokens: Vec<LexItem>) -> Result<ParserResult, String> {
     let mut leases = Leases::new();
+    parse_leases(tokens, |l| leases.push(l))?;
+
+    Ok(ParserResult { leases: leases })
+}
+
+/// Parses the declarations, handing every lease to `f` as soon as it is parsed
+#[allow(clippy::cmp_owned)]
+fn parse_leases<F: FnMut(Lease)>(tokens: Vec<LexItem>, mut f: F) -> Result<(), String> {
     let lease = Lease::new();
 
     let mut it = tokens.iter().peekable();
@@ -40,17 +59,20 @@ fn parse_config(tokens: Vec<LexItem>) -> Result<ParserResult, String> {
         match token {
             LexItem::Decl(ConfigKeyword::Lease) => {
                 if lease != Lease::new() {
-                    leases.push(lease.clone());
+                    f(lease.clone());
                 }
 
                 let mut lease = Lease::new();
                 // ip-address
                 it.next();
-                lease.ip = it.peek().expect("IP address expected").to_string();
+                lease.ip = it.peek().ok_or("IP address expected")?.to_string();
 
                 // left curly brace
                 it.next();
-                assert_eq!(it.peek().unwrap().to_owned(), &LexItem::Paren('{'));
+                match it.peek() {
+                    Some(LexItem::Paren('{')) => (),
+                    t => return Err(format!("Expected start of section with '{{', got '{:?}'", t)),
+                }
 
                 // statements for the lease
                 it.next();
@@ -64,7 +86,7 @@ fn parse_config(tokens: Vec<LexItem>) -> Result<ParserResult, String> {
                     ));
                 }
 
-                leases.push(lease.clone());
+                f(lease);
                 it.next();
             }
             _ => {
@@ -73,9 +95,10 @@ fn parse_config(tokens: Vec<LexItem>) -> Result<ParserResult, String> {
         }
     }
 
-    Ok(ParserResult { leases: leases })
+    Ok(())
 }
 
+#[allow(clippy::needless_return)]
 pub fn parse<S>(input: S) -> Result<ParserResult, String>
 where
     S: Into<String>,
@@ -83,3 +106,55 @@ where
     let tokens = lex(input).unwrap();
     return parse_config(tokens);
 }
+
+/// Parses the input like [`parse`], handing every lease to `f` instead of
+/// collecting them. The input is lexed one declaration at a time, so only the
+/// items of the current declaration are held.
+pub(crate) fn parse_with<S, F>(input: S, mut f: F) -> Result<(), ParseError>
+where
+    S: Into<String>,
+    F: FnMut(Lease),
+{
+    let input = input.into();
+
+    let mut tokens = Vec::new();
+    for token in Lexer::new(input.chars()) {
+        let end = token == LexItem::Paren('}');
+        tokens.push(token);
+        if end {
+            parse_leases(std::mem::take(&mut tokens), &mut f)?;
+        }
+    }
+
+    parse_leases(tokens, f)
+}
+
+/// Reads and parses the leases file at `path`, see
+/// [`decompress`](crate::decompress::decompress) for compressed files
+pub fn parse_path<P: AsRef<Path>>(path: P) -> Result<ParserResult, ParseError> {
+    let path = path.as_ref();
+
+    let mut input = String::new();
+    decompress::open(path)?
+        .read_to_string(&mut input)
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    parse(input)
+}
+
+/// Reads the whole input from an async reader and parses it
+#[cfg(feature = "tokio")]
+pub async fn parse_async<R>(mut reader: R) -> Result<ParserResult, ParseError>
+where
+    R: tokio::io::AsyncRead + Unpin,
+{
+    use tokio::io::AsyncReadExt;
+
+    let mut input = String::new();
+    reader
+        .read_to_string(&mut input)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    parse(input)
+}
@@ -0,0 +1,330 @@
+//! Small regular expression engine for lease queries.
+//!
+//! Supports literals, `.`, character classes with ranges and negation, the
+//! `\d`, `\w` and `\s` classes, escaped characters, the `*`, `+` and `?`
+//! quantifiers, groups, `|` alternatives and the `^` and `$` anchors. Patterns
+//! are compiled to an NFA which is simulated in linear time of the input.
+//!
+//! Queries only need this subset, and a few hundred lines keep the crate free
+//! of a required dependency on the `regex` crate and its Unicode tables.
+//! Queries may come from untrusted input, so matching never backtracks and
+//! groups are nested at most [`MAX_DEPTH`] levels.
+
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// Deepest nesting of groups in a pattern
+const MAX_DEPTH: usize = 64;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Class {
+    Any,
+    Char(char),
+    Set {
+        negated: bool,
+        ranges: Vec<(char, char)>,
+    },
+}
+
+impl Class {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            Class::Any => true,
+            Class::Char(x) => *x == c,
+            Class::Set { negated, ranges } => {
+                ranges.iter().any(|(lo, hi)| *lo <= c && c <= *hi) != *negated
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Repeat {
+    ZeroOrMore,
+    OneOrMore,
+    ZeroOrOne,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Node {
+    Class(Class),
+    Start,
+    End,
+    Alt(Vec<Vec<Node>>),
+    Repeat(Box<Node>, Repeat),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Inst {
+    Class(Class),
+    Start,
+    End,
+    Split(usize, usize),
+    Jmp(usize),
+    Match,
+}
+
+/// Compiled regular expression, matches anywhere in the input unless anchored
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Pattern {
+    source: String,
+    prog: Vec<Inst>,
+}
+
+impl Pattern {
+    pub(crate) fn new(source: &str) -> Result<Pattern, String> {
+        let mut chars = source.chars().peekable();
+        let node =
+            parse_alt(&mut chars, 0).map_err(|e| format!("Invalid pattern {:?}: {}", source, e))?;
+
+        let mut prog = Vec::new();
+        compile(&node, &mut prog);
+        prog.push(Inst::Match);
+
+        Ok(Pattern {
+            source: source.to_owned(),
+            prog,
+        })
+    }
+
+    pub(crate) fn is_match(&self, s: &str) -> bool {
+        let chars: Vec<char> = s.chars().collect();
+        let mut current = Vec::new();
+        let mut next = Vec::new();
+        let mut seen = vec![usize::MAX; self.prog.len()];
+        let mut stack = Vec::new();
+
+        for pos in 0..=chars.len() {
+            // a new thread starts at every position, so the match is unanchored
+            if self.add(&mut current, &mut seen, &mut stack, 0, pos, chars.len()) {
+                return true;
+            }
+            if pos == chars.len() {
+                break;
+            }
+
+            for &pc in current.iter() {
+                if let Inst::Class(c) = &self.prog[pc] {
+                    if c.matches(chars[pos])
+                        && self.add(
+                            &mut next,
+                            &mut seen,
+                            &mut stack,
+                            pc + 1,
+                            pos + 1,
+                            chars.len(),
+                        )
+                    {
+                        return true;
+                    }
+                }
+            }
+            current.clear();
+            std::mem::swap(&mut current, &mut next);
+        }
+
+        false
+    }
+
+    /// Adds the thread at `pc` and all threads reachable from it without
+    /// consuming input, returns whether the match instruction was reached.
+    /// The instructions still to follow are kept on `stack` rather than
+    /// recursing, as a long pattern can chain many splits.
+    fn add(
+        &self,
+        list: &mut Vec<usize>,
+        seen: &mut [usize],
+        stack: &mut Vec<usize>,
+        pc: usize,
+        pos: usize,
+        len: usize,
+    ) -> bool {
+        stack.clear();
+        stack.push(pc);
+
+        while let Some(pc) = stack.pop() {
+            if seen[pc] == pos {
+                continue;
+            }
+            seen[pc] = pos;
+
+            match self.prog[pc] {
+                Inst::Match => return true,
+                Inst::Jmp(x) => stack.push(x),
+                Inst::Split(x, y) => {
+                    stack.push(y);
+                    stack.push(x);
+                }
+                Inst::Start if pos == 0 => stack.push(pc + 1),
+                Inst::End if pos == len => stack.push(pc + 1),
+                Inst::Start | Inst::End => {}
+                Inst::Class(_) => list.push(pc),
+            }
+        }
+
+        false
+    }
+}
+
+fn parse_alt(chars: &mut Peekable<Chars>, depth: usize) -> Result<Node, String> {
+    if depth > MAX_DEPTH {
+        return Err(format!("groups nested deeper than {} levels", MAX_DEPTH));
+    }
+
+    let mut branches = vec![parse_seq(chars, depth)?];
+    while chars.peek() == Some(&'|') {
+        chars.next();
+        branches.push(parse_seq(chars, depth)?);
+    }
+
+    match chars.peek() {
+        Some(')') if depth == 0 => Err("unmatched )".to_owned()),
+        _ => Ok(Node::Alt(branches)),
+    }
+}
+
+fn parse_seq(chars: &mut Peekable<Chars>, depth: usize) -> Result<Vec<Node>, String> {
+    let mut seq = Vec::new();
+    while let Some(&c) = chars.peek() {
+        if c == '|' || c == ')' {
+            break;
+        }
+        chars.next();
+
+        let node = match c {
+            '(' => {
+                let node = parse_alt(chars, depth + 1)?;
+                if chars.next() != Some(')') {
+                    return Err("unclosed (".to_owned());
+                }
+                node
+            }
+            '[' => Node::Class(parse_set(chars)?),
+            '.' => Node::Class(Class::Any),
+            '^' => Node::Start,
+            '$' => Node::End,
+            '\\' => Node::Class(parse_escape(chars)?),
+            '*' | '+' | '?' => return Err(format!("nothing to repeat before {}", c)),
+            c => Node::Class(Class::Char(c)),
+        };
+
+        let repeat = match chars.peek() {
+            Some('*') => Some(Repeat::ZeroOrMore),
+            Some('+') => Some(Repeat::OneOrMore),
+            Some('?') => Some(Repeat::ZeroOrOne),
+            _ => None,
+        };
+        match repeat {
+            Some(r) => {
+                chars.next();
+                seq.push(Node::Repeat(Box::new(node), r));
+            }
+            None => seq.push(node),
+        }
+    }
+
+    Ok(seq)
+}
+
+fn parse_escape(chars: &mut Peekable<Chars>) -> Result<Class, String> {
+    let ranges = match chars.next() {
+        Some('d') => vec![('0', '9')],
+        Some('w') => vec![('a', 'z'), ('A', 'Z'), ('0', '9'), ('_', '_')],
+        Some('s') => vec![(' ', ' '), ('\t', '\t'), ('\n', '\n'), ('\r', '\r')],
+        Some(c) => return Ok(Class::Char(c)),
+        None => return Err("trailing \\".to_owned()),
+    };
+
+    Ok(Class::Set {
+        negated: false,
+        ranges,
+    })
+}
+
+fn parse_set(chars: &mut Peekable<Chars>) -> Result<Class, String> {
+    let negated = chars.peek() == Some(&'^');
+    if negated {
+        chars.next();
+    }
+
+    let mut ranges = Vec::new();
+    let mut first = true;
+    loop {
+        let lo = match chars.next() {
+            Some(']') if !first => break,
+            Some('\\') => match parse_escape(chars)? {
+                Class::Char(c) => c,
+                Class::Set { ranges: r, .. } => {
+                    ranges.extend(r);
+                    first = false;
+                    continue;
+                }
+                Class::Any => unreachable!(),
+            },
+            Some(c) => c,
+            None => return Err("unclosed [".to_owned()),
+        };
+        first = false;
+
+        let mut ahead = chars.clone();
+        match (ahead.next(), ahead.next()) {
+            (Some('-'), Some(hi)) if hi != ']' => {
+                chars.next();
+                chars.next();
+                if hi < lo {
+                    return Err(format!("invalid range {}-{}", lo, hi));
+                }
+                ranges.push((lo, hi));
+            }
+            _ => ranges.push((lo, lo)),
+        }
+    }
+
+    Ok(Class::Set { negated, ranges })
+}
+
+fn compile(node: &Node, prog: &mut Vec<Inst>) {
+    match node {
+        Node::Class(c) => prog.push(Inst::Class(c.clone())),
+        Node::Start => prog.push(Inst::Start),
+        Node::End => prog.push(Inst::End),
+        Node::Alt(branches) => {
+            let mut jumps = Vec::new();
+            for (i, branch) in branches.iter().enumerate() {
+                let split = prog.len();
+                if i + 1 < branches.len() {
+                    prog.push(Inst::Split(split + 1, 0));
+                }
+                for n in branch {
+                    compile(n, prog);
+                }
+                if i + 1 < branches.len() {
+                    jumps.push(prog.len());
+                    prog.push(Inst::Jmp(0));
+                    prog[split] = Inst::Split(split + 1, prog.len());
+                }
+            }
+            for j in jumps {
+                prog[j] = Inst::Jmp(prog.len());
+            }
+        }
+        Node::Repeat(n, Repeat::ZeroOrMore) => {
+            let split = prog.len();
+            prog.push(Inst::Split(split + 1, 0));
+            compile(n, prog);
+            prog.push(Inst::Jmp(split));
+            prog[split] = Inst::Split(split + 1, prog.len());
+        }
+        Node::Repeat(n, Repeat::OneOrMore) => {
+            let start = prog.len();
+            compile(n, prog);
+            prog.push(Inst::Split(start, prog.len() + 1));
+        }
+        Node::Repeat(n, Repeat::ZeroOrOne) => {
+            let split = prog.len();
+            prog.push(Inst::Split(split + 1, 0));
+            compile(n, prog);
+            prog[split] = Inst::Split(split + 1, prog.len());
+        }
+    }
+}
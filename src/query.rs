@@ -0,0 +1,462 @@
+//! Selection of leases, built with [`LeaseFilter`] methods or parsed from a
+//! query expression like
+//!
+//! ```text
+//! mac ~ "^00:1a" and active at "2026-10-01T12:00:00Z" and not abandoned
+//!     and ip in 10.0.0.0/22
+//! ```
+//!
+//! Conditions:
+//!
+//! - `<field> = <value>`, `<field> != <value>` compare a field of the lease,
+//!   fields are named like [`Column`]s, except `state` and `abandoned`. MAC
+//!   addresses are compared case-insensitively.
+//! - `<field> ~ <pattern>`, `<field> !~ <pattern>` match a field against a
+//!   regular expression, see below.
+//! - `starts` and `ends` are compared to RFC3339 dates, or `now`, with `=`,
+//!   `!=`, `<`, `<=`, `>` and `>=`.
+//! - `active at <date>` holds for leases which are neither abandoned nor
+//!   expired nor starting later at the date.
+//! - `abandoned` holds for abandoned leases.
+//! - `ip in <network>` holds for addresses in a network in CIDR notation.
+//!
+//! Conditions are combined with `and`, `or`, `not` and parentheses. A missing
+//! field never equals nor matches anything. Values containing spaces or
+//! parentheses have to be double quoted, `\"` and `\\` escape within quotes.
+//!
+//! Regular expressions support literals, `.`, character classes like `[0-9a-f]`
+//! and `[^:]`, the `\d`, `\w` and `\s` classes, the `*`, `+` and `?`
+//! quantifiers, groups, `|` alternatives and the `^` and `$` anchors.
+
+use std::cmp::Ordering;
+use std::ops::Not;
+
+use crate::common::Date;
+use crate::export::Column;
+use crate::leases::{Lease, LeaseState, Leases, LeasesMethods};
+use crate::net::Prefix;
+use crate::pattern::Pattern;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Expr {
+    Any,
+    Equals(Column, String),
+    Matches(Column, Pattern),
+    Compare(Column, Ordering, bool, Date),
+    ActiveAt(Date),
+    Abandoned,
+    IpIn(Prefix),
+    /// Conjunction of at least two expressions, none of them an `And`
+    And(Vec<Expr>),
+    /// Disjunction of at least two expressions, none of them an `Or`
+    Or(Vec<Expr>),
+    Not(Box<Expr>),
+}
+
+/// Condition leases are selected by, see the [module](self) documentation
+/// for the query syntax
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LeaseFilter(Expr);
+
+impl LeaseFilter {
+    /// Parses a query expression
+    pub fn from(query: &str) -> Result<LeaseFilter, String> {
+        let tokens = tokenize(query)?;
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            depth: 0,
+        };
+        let expr = parser.or()?;
+        match parser.tokens.get(parser.pos) {
+            None => Ok(LeaseFilter(expr)),
+            Some(t) => Err(format!("Unexpected {} in query", t)),
+        }
+    }
+
+    /// Selects every lease
+    pub fn any() -> LeaseFilter {
+        LeaseFilter(Expr::Any)
+    }
+
+    pub fn equals<S: Into<String>>(field: Column, value: S) -> Result<LeaseFilter, String> {
+        let value = value.into();
+        match field {
+            Column::State => Err(state_error()),
+            Column::Abandoned => Err(abandoned_error()),
+            Column::Starts | Column::Ends => {
+                let date = parse_date(&value)?;
+                Ok(LeaseFilter(Expr::Compare(
+                    field,
+                    Ordering::Equal,
+                    true,
+                    date,
+                )))
+            }
+            _ => Ok(LeaseFilter(Expr::Equals(field, value))),
+        }
+    }
+
+    /// Selects leases whose field matches the regular expression
+    pub fn matches(field: Column, pattern: &str) -> Result<LeaseFilter, String> {
+        if field == Column::State {
+            return Err(state_error());
+        }
+        if field == Column::Abandoned {
+            return Err(abandoned_error());
+        }
+
+        Ok(LeaseFilter(Expr::Matches(field, Pattern::new(pattern)?)))
+    }
+
+    /// Selects leases whose `starts` or `ends` is before `date`
+    pub fn before(field: Column, date: Date) -> Result<LeaseFilter, String> {
+        LeaseFilter::compare(field, Ordering::Less, false, date)
+    }
+
+    /// Selects leases whose `starts` or `ends` is after `date`
+    pub fn after(field: Column, date: Date) -> Result<LeaseFilter, String> {
+        LeaseFilter::compare(field, Ordering::Greater, false, date)
+    }
+
+    fn compare(
+        field: Column,
+        ord: Ordering,
+        or_equal: bool,
+        date: Date,
+    ) -> Result<LeaseFilter, String> {
+        match field {
+            Column::Starts | Column::Ends => {
+                Ok(LeaseFilter(Expr::Compare(field, ord, or_equal, date)))
+            }
+            f => Err(format!("{} is not a date and can't be ordered", f)),
+        }
+    }
+
+    pub fn active_at(date: Date) -> LeaseFilter {
+        LeaseFilter(Expr::ActiveAt(date))
+    }
+
+    pub fn abandoned() -> LeaseFilter {
+        LeaseFilter(Expr::Abandoned)
+    }
+
+    pub fn ip_in(network: Prefix) -> LeaseFilter {
+        LeaseFilter(Expr::IpIn(network))
+    }
+
+    pub fn and(self, other: LeaseFilter) -> LeaseFilter {
+        match (self.0, other.0) {
+            (Expr::Any, e) | (e, Expr::Any) => LeaseFilter(e),
+            (a, b) => {
+                let mut terms = and_terms(a);
+                terms.extend(and_terms(b));
+                LeaseFilter(Expr::And(terms))
+            }
+        }
+    }
+
+    pub fn or(self, other: LeaseFilter) -> LeaseFilter {
+        let mut terms = or_terms(self.0);
+        terms.extend(or_terms(other.0));
+        LeaseFilter(Expr::Or(terms))
+    }
+
+    /// Returns whether the lease is selected
+    pub fn is_match(&self, l: &Lease) -> bool {
+        eval(&self.0, l)
+    }
+
+    /// Returns the selected leases in their original order
+    pub fn filter(&self, leases: &Leases) -> Leases {
+        let mut res = Leases::new();
+        for l in leases.iter().filter(|l| self.is_match(l)) {
+            res.push(l.clone());
+        }
+
+        res
+    }
+}
+
+impl Default for LeaseFilter {
+    fn default() -> LeaseFilter {
+        LeaseFilter::any()
+    }
+}
+
+impl Not for LeaseFilter {
+    type Output = LeaseFilter;
+
+    fn not(self) -> LeaseFilter {
+        LeaseFilter(Expr::Not(Box::new(self.0)))
+    }
+}
+
+/// Returns the terms of a conjunction, so that long chains of `and` are kept
+/// flat instead of nesting deeper with every term
+fn and_terms(e: Expr) -> Vec<Expr> {
+    match e {
+        Expr::And(terms) => terms,
+        e => vec![e],
+    }
+}
+
+/// Returns the terms of a disjunction, see [`and_terms`]
+fn or_terms(e: Expr) -> Vec<Expr> {
+    match e {
+        Expr::Or(terms) => terms,
+        e => vec![e],
+    }
+}
+
+fn state_error() -> String {
+    "state depends on the time, use `active at <date>` or `abandoned`".to_owned()
+}
+
+fn abandoned_error() -> String {
+    "abandoned is not a string, use `abandoned` or `not abandoned`".to_owned()
+}
+
+fn eval(e: &Expr, l: &Lease) -> bool {
+    match e {
+        Expr::Any => true,
+        Expr::Equals(Column::Mac, v) => {
+            field(Column::Mac, l).is_some_and(|m| m.eq_ignore_ascii_case(v))
+        }
+        Expr::Equals(c, v) => field(*c, l).is_some_and(|s| s == v),
+        Expr::Matches(c, p) => field(*c, l).is_some_and(|s| p.is_match(s)),
+        Expr::Compare(c, ord, or_equal, date) => {
+            let value = match c {
+                Column::Starts => l.dates.starts,
+                _ => l.dates.ends,
+            };
+            value.is_some_and(|v| {
+                let o = v.cmp(date);
+                o == *ord || (*or_equal && o == Ordering::Equal)
+            })
+        }
+        Expr::ActiveAt(d) => l.state_at(*d) == LeaseState::Active,
+        Expr::Abandoned => l.abandoned,
+        Expr::IpIn(net) => net.contains_str(&l.ip),
+        Expr::And(terms) => terms.iter().all(|e| eval(e, l)),
+        Expr::Or(terms) => terms.iter().any(|e| eval(e, l)),
+        Expr::Not(a) => !eval(a, l),
+    }
+}
+
+/// Returns a string field, dates are not strings
+fn field(c: Column, l: &Lease) -> Option<&str> {
+    match c {
+        Column::Ip => Some(&l.ip),
+        Column::Mac => l.hardware.as_ref().map(|hw| hw.mac.as_str()),
+        Column::Uid => l.uid.as_deref(),
+        Column::Hostname => l.hostname.as_deref(),
+        Column::ClientHostname => l.client_hostname.as_deref(),
+        Column::Starts | Column::Ends | Column::Abandoned | Column::State => None,
+    }
+}
+
+/// Parses RFC3339 UTC date or `now`, as used in queries
+pub fn parse_date(s: &str) -> Result<Date, String> {
+    if s == "now" {
+        return Ok(Date::now());
+    }
+
+    // the weekday is not part of RFC3339, so it is computed from the date
+    let d = Date::from_rfc3339(0, s)?;
+    Ok(Date::from_timestamp(d.timestamp()))
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    Op(&'static str),
+    Open,
+    Close,
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Token::Word(w) => write!(f, "'{}'", w),
+            Token::Quoted(q) => write!(f, "{:?}", q),
+            Token::Op(o) => write!(f, "'{}'", o),
+            Token::Open => write!(f, "'('"),
+            Token::Close => write!(f, "')'"),
+        }
+    }
+}
+
+const OPERATORS: [&str; 8] = ["!=", "!~", "<=", ">=", "=", "~", "<", ">"];
+
+/// Deepest nesting of parentheses and `not` in a query
+const MAX_DEPTH: usize = 64;
+
+fn tokenize(query: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = query.trim_start();
+
+    while let Some(c) = rest.chars().next() {
+        if c == '(' || c == ')' {
+            tokens.push(if c == '(' { Token::Open } else { Token::Close });
+            rest = &rest[1..];
+        } else if c == '"' {
+            let mut value = String::new();
+            let mut chars = rest[1..].char_indices();
+            let end = loop {
+                match chars.next() {
+                    Some((i, '"')) => break i + 2,
+                    Some((_, '\\')) => match chars.next() {
+                        Some((_, c)) => value.push(c),
+                        None => return Err("Unclosed quote in query".to_owned()),
+                    },
+                    Some((_, c)) => value.push(c),
+                    None => return Err("Unclosed quote in query".to_owned()),
+                }
+            };
+            tokens.push(Token::Quoted(value));
+            rest = &rest[end..];
+        } else if let Some(op) = OPERATORS.iter().find(|op| rest.starts_with(*op)) {
+            tokens.push(Token::Op(op));
+            rest = &rest[op.len()..];
+        } else {
+            let end = rest
+                .find(|c: char| c.is_whitespace() || "()\"=!~<>".contains(c))
+                .unwrap_or(rest.len());
+            if end == 0 {
+                return Err(format!("Unexpected '{}' in query", c));
+            }
+            tokens.push(Token::Word(rest[..end].to_owned()));
+            rest = &rest[end..];
+        }
+        rest = rest.trim_start();
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    /// Current nesting of parentheses and `not`
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Result<Token, String> {
+        let t = self.peek().cloned().ok_or("Unexpected end of query")?;
+        self.pos += 1;
+
+        Ok(t)
+    }
+
+    fn keyword(&mut self, k: &str) -> bool {
+        if self.peek() == Some(&Token::Word(k.to_owned())) {
+            self.pos += 1;
+            return true;
+        }
+
+        false
+    }
+
+    fn value(&mut self) -> Result<String, String> {
+        match self.next()? {
+            Token::Word(w) | Token::Quoted(w) => Ok(w),
+            t => Err(format!("Expected a value, found {}", t)),
+        }
+    }
+
+    /// Parses with `f` one level deeper, failing beyond [`MAX_DEPTH`]
+    fn nested<F>(&mut self, f: F) -> Result<Expr, String>
+    where
+        F: FnOnce(&mut Parser) -> Result<Expr, String>,
+    {
+        if self.depth == MAX_DEPTH {
+            return Err(format!("Query is nested deeper than {} levels", MAX_DEPTH));
+        }
+
+        self.depth += 1;
+        let res = f(self);
+        self.depth -= 1;
+
+        res
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut e = LeaseFilter(self.and()?);
+        while self.keyword("or") {
+            e = e.or(LeaseFilter(self.and()?));
+        }
+
+        Ok(e.0)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut e = LeaseFilter(self.unary()?);
+        while self.keyword("and") {
+            e = e.and(LeaseFilter(self.unary()?));
+        }
+
+        Ok(e.0)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.keyword("not") {
+            return Ok(Expr::Not(Box::new(self.nested(Parser::unary)?)));
+        }
+
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        if self.peek() == Some(&Token::Open) {
+            self.pos += 1;
+            let e = self.nested(Parser::or)?;
+            return match self.next()? {
+                Token::Close => Ok(e),
+                t => Err(format!("Expected ')', found {}", t)),
+            };
+        }
+        if self.keyword("abandoned") {
+            return Ok(Expr::Abandoned);
+        }
+        if self.keyword("active") {
+            if !self.keyword("at") {
+                return Err("Expected 'at' after 'active'".to_owned());
+            }
+            return Ok(Expr::ActiveAt(parse_date(&self.value()?)?));
+        }
+
+        let field = match self.next()? {
+            Token::Word(w) => Column::from(&w)?,
+            t => return Err(format!("Expected a field, found {}", t)),
+        };
+        if field == Column::Ip && self.keyword("in") {
+            return Ok(Expr::IpIn(Prefix::from(&self.value()?)?));
+        }
+
+        let op = match self.next()? {
+            Token::Op(op) => op,
+            t => return Err(format!("Expected an operator after {}, found {}", field, t)),
+        };
+        let value = self.value()?;
+
+        let f = match op {
+            "=" => LeaseFilter::equals(field, value)?,
+            "!=" => !LeaseFilter::equals(field, value)?,
+            "~" => LeaseFilter::matches(field, &value)?,
+            "!~" => !LeaseFilter::matches(field, &value)?,
+            "<" => LeaseFilter::compare(field, Ordering::Less, false, parse_date(&value)?)?,
+            "<=" => LeaseFilter::compare(field, Ordering::Less, true, parse_date(&value)?)?,
+            ">" => LeaseFilter::compare(field, Ordering::Greater, false, parse_date(&value)?)?,
+            _ => LeaseFilter::compare(field, Ordering::Greater, true, parse_date(&value)?)?,
+        };
+
+        Ok(f.0)
+    }
+}
@@ -0,0 +1,199 @@
+use std::collections::VecDeque;
+use std::io::Read;
+use std::path::Path;
+use std::str;
+
+use crate::decompress;
+use crate::leases::Lease;
+use crate::parser;
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Accumulates input and splits off the part made of complete top level
+/// declarations, the rest is kept until more input arrives.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct BlockBuffer {
+    text: String,
+    // trailing bytes of a character split between two reads
+    partial_char: Vec<u8>,
+    // scan state for `text[scanned..]`
+    scanned: usize,
+    depth: usize,
+    in_quotes: bool,
+    complete: usize,
+}
+
+impl BlockBuffer {
+    pub(crate) fn push(&mut self, bytes: &[u8]) -> Result<(), String> {
+        let mut bytes = bytes;
+        let joined;
+        if !self.partial_char.is_empty() {
+            let mut v = std::mem::take(&mut self.partial_char);
+            v.extend_from_slice(bytes);
+            joined = v;
+            bytes = &joined;
+        }
+
+        match str::from_utf8(bytes) {
+            Ok(s) => self.push_str(s),
+            Err(e) if e.error_len().is_none() => {
+                let (valid, rest) = bytes.split_at(e.valid_up_to());
+                self.push_str(str::from_utf8(valid).unwrap());
+                self.partial_char = rest.to_vec();
+            }
+            Err(e) => return Err(format!("Input is not valid UTF-8: {}", e)),
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn push_str(&mut self, s: &str) {
+        self.text.push_str(s);
+        self.scan();
+    }
+
+    fn scan(&mut self) {
+        for (i, c) in self.text[self.scanned..].char_indices() {
+            match c {
+                '"' => self.in_quotes = !self.in_quotes,
+                '{' if !self.in_quotes => self.depth += 1,
+                '}' if !self.in_quotes && self.depth > 0 => {
+                    self.depth -= 1;
+                    if self.depth == 0 {
+                        self.complete = self.scanned + i + 1;
+                    }
+                }
+                _ => (),
+            }
+        }
+        self.scanned = self.text.len();
+    }
+
+    /// Removes and returns all complete declarations
+    pub(crate) fn take_complete(&mut self) -> Option<String> {
+        if self.complete == 0 {
+            return None;
+        }
+
+        let rest = self.text.split_off(self.complete);
+        let res = std::mem::replace(&mut self.text, rest);
+        self.scanned -= self.complete;
+        self.complete = 0;
+
+        Some(res)
+    }
+
+    /// Returns input which does not form a complete declaration yet
+    pub(crate) fn pending(&self) -> &str {
+        &self.text
+    }
+}
+
+/// Returns byte offsets of top level `lease` declarations in `text`, which
+/// match the leases `parser::parse` returns for it one to one
+pub(crate) fn declaration_offsets(text: &str) -> Vec<usize> {
+    let mut res = Vec::new();
+    let mut depth = 0;
+    let mut in_quotes = false;
+    let mut word_start = true;
+
+    for (i, c) in text.char_indices() {
+        if depth == 0 && !in_quotes && word_start && text[i..].starts_with("lease") {
+            let next = text[i + 5..].chars().next();
+            if next.is_none_or(|c| c.is_whitespace()) {
+                res.push(i);
+            }
+        }
+
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '{' if !in_quotes => depth += 1,
+            '}' if !in_quotes && depth > 0 => depth -= 1,
+            _ => (),
+        }
+        word_start = c.is_whitespace() || c == '}' || c == ';';
+    }
+
+    res
+}
+
+/// Returns the lines, starting at 1, of the top level `lease` declarations in
+/// `text`, which match the leases `parser::parse` returns for it one to one
+pub fn declaration_lines(text: &str) -> Vec<usize> {
+    let mut res = Vec::new();
+    let (mut line, mut last) = (1, 0);
+    for offset in declaration_offsets(text) {
+        line += text[last..offset].matches('\n').count();
+        last = offset;
+        res.push(line);
+    }
+
+    res
+}
+
+/// Parses leases from a reader incrementally, without loading the whole input
+/// into memory first.
+pub struct LeaseReader<R: Read> {
+    reader: R,
+    buffer: BlockBuffer,
+    parsed: VecDeque<Lease>,
+    done: bool,
+}
+
+impl LeaseReader<Box<dyn Read>> {
+    /// Opens the leases file at `path`, decompressing it if necessary
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<LeaseReader<Box<dyn Read>>, String> {
+        Ok(LeaseReader::new(decompress::open(path)?))
+    }
+}
+
+impl<R: Read> LeaseReader<R> {
+    pub fn new(reader: R) -> LeaseReader<R> {
+        LeaseReader {
+            reader,
+            buffer: BlockBuffer::default(),
+            parsed: VecDeque::new(),
+            done: false,
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    fn fill(&mut self) -> Result<(), String> {
+        let mut chunk = vec![0; CHUNK_SIZE];
+        while self.parsed.is_empty() {
+            let n = self.reader.read(&mut chunk).map_err(|e| e.to_string())?;
+            if n == 0 {
+                self.done = true;
+                if !self.buffer.pending().trim().is_empty() {
+                    return Err("Incomplete declaration at the end of input".to_owned());
+                }
+                return Ok(());
+            }
+
+            self.buffer.push(&chunk[..n])?;
+            if let Some(text) = self.buffer.take_complete() {
+                self.parsed.extend(parser::parse(text)?.leases.iter().cloned());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<R: Read> Iterator for LeaseReader<R> {
+    type Item = Result<Lease, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.parsed.is_empty() && !self.done {
+            if let Err(e) = self.fill() {
+                self.done = true;
+                return Some(Err(e));
+            }
+        }
+
+        self.parsed.pop_front().map(Ok)
+    }
+}
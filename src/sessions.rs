@@ -0,0 +1,175 @@
+//! Reconstruction of device sessions from the leases journal.
+//!
+//! Devices are identified by their [`ClientId`]. A session is a run of
+//! declarations of the same IP address where each one starts before the
+//! previous one ended, or within [`SessionOptions::max_gap`] after that.
+//! Renewals and early releases are handled like in [`conflicts`](crate::conflicts).
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::common::Date;
+use crate::conflicts::held;
+use crate::leases::{ClientId, Lease, Leases, LeasesMethods};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SessionOptions {
+    /// Longest gap between declarations which doesn't end a session
+    pub max_gap: Duration,
+    /// Leases without `ends` or ending later are cut off at this date
+    pub now: Date,
+}
+
+impl Default for SessionOptions {
+    fn default() -> SessionOptions {
+        SessionOptions {
+            max_gap: Duration::from_secs(0),
+            now: Date::now(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Session {
+    pub ip: String,
+    pub first_seen: Date,
+    pub last_seen: Date,
+    /// Time covered by the declarations, gaps excluded
+    pub connected: Duration,
+    /// Distinct hostnames and client hostnames in order of appearance
+    pub hostnames: Vec<String>,
+    /// Indices of the declarations of the session
+    pub leases: Vec<usize>,
+}
+
+/// Sessions of one device, ordered by `first_seen`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeviceHistory {
+    pub client: ClientId,
+    pub sessions: Vec<Session>,
+}
+
+impl DeviceHistory {
+    pub fn first_seen(&self) -> Option<Date> {
+        self.sessions.first().map(|s| s.first_seen)
+    }
+
+    pub fn last_seen(&self) -> Option<Date> {
+        self.sessions.iter().map(|s| s.last_seen).max()
+    }
+
+    pub fn connected(&self) -> Duration {
+        self.sessions.iter().map(|s| s.connected).sum()
+    }
+
+    /// Distinct hostnames of all sessions in order of appearance
+    pub fn hostnames(&self) -> Vec<&str> {
+        let mut res: Vec<&str> = Vec::new();
+        for hn in self.sessions.iter().flat_map(|s| s.hostnames.iter()) {
+            if !res.contains(&hn.as_str()) {
+                res.push(hn);
+            }
+        }
+
+        res
+    }
+}
+
+/// Returns the sessions of every device, ordered by client. Declarations
+/// without `starts`, a hardware address or uid and abandoned ones are left out.
+pub fn devices(leases: &Leases, options: &SessionOptions) -> Vec<DeviceHistory> {
+    let now = options.now.timestamp();
+    let mut by_client: BTreeMap<ClientId, Vec<(usize, &Lease, i64, i64)>> = BTreeMap::new();
+
+    for (i, l, start, end) in held(leases) {
+        if start == i64::MIN || start > now {
+            continue;
+        }
+        if let Some(client) = l.client_id() {
+            by_client
+                .entry(client)
+                .or_default()
+                .push((i, l, start, end.min(now)));
+        }
+    }
+
+    by_client
+        .into_iter()
+        .map(|(client, mut held)| {
+            held.sort_by_key(|&(i, _, start, _)| (start, i));
+            DeviceHistory {
+                client,
+                sessions: sessions(&held, options.max_gap.as_secs() as i64),
+            }
+        })
+        .collect()
+}
+
+/// Returns the sessions of a single device
+pub fn device(leases: &Leases, client: &ClientId, options: &SessionOptions) -> DeviceHistory {
+    let mut own = Leases::new();
+    let mut indices = Vec::new();
+    for (i, l) in leases.iter().enumerate() {
+        if l.client_id().as_ref() == Some(client) {
+            own.push(l.clone());
+            indices.push(i);
+        }
+    }
+
+    let mut history = devices(&own, options).pop().unwrap_or(DeviceHistory {
+        client: client.clone(),
+        sessions: Vec::new(),
+    });
+    for s in history.sessions.iter_mut() {
+        for i in s.leases.iter_mut() {
+            *i = indices[*i];
+        }
+    }
+
+    history
+}
+
+fn sessions(held: &[(usize, &Lease, i64, i64)], max_gap: i64) -> Vec<Session> {
+    let mut res: Vec<Session> = Vec::new();
+    let mut last_end = i64::MIN;
+
+    for &(i, l, start, end) in held {
+        let continues = res
+            .last()
+            .is_some_and(|s| s.ip == l.ip && start <= last_end.saturating_add(max_gap));
+
+        if continues {
+            let s = res.last_mut().unwrap();
+            let connected = end - start.max(last_end);
+            if connected > 0 {
+                s.connected += Duration::from_secs(connected as u64);
+            }
+            last_end = last_end.max(end);
+            s.last_seen = Date::from_timestamp(last_end);
+            s.leases.push(i);
+            add_hostnames(s, l);
+        } else {
+            let mut s = Session {
+                ip: l.ip.clone(),
+                first_seen: Date::from_timestamp(start),
+                last_seen: Date::from_timestamp(end),
+                connected: Duration::from_secs((end - start) as u64),
+                hostnames: Vec::new(),
+                leases: vec![i],
+            };
+            add_hostnames(&mut s, l);
+            res.push(s);
+            last_end = end;
+        }
+    }
+
+    res
+}
+
+fn add_hostnames(s: &mut Session, l: &Lease) {
+    for hn in [&l.client_hostname, &l.hostname].iter().copied().flatten() {
+        if !s.hostnames.contains(hn) {
+            s.hostnames.push(hn.clone());
+        }
+    }
+}
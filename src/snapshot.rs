@@ -0,0 +1,370 @@
+//! Compact binary snapshot of parsed leases.
+//!
+//! Loading a snapshot skips lexing and parsing of the leases file entirely.
+//! The header records size and modification time of the leases file the
+//! snapshot was made from, so a stale snapshot can be detected without reading
+//! the rest of it.
+//!
+//! Layout, integers are LEB128 varints (signed ones zigzag encoded) unless
+//! noted otherwise:
+//!
+//! - magic `DHCPDLS\0`, format version as little endian `u32`
+//! - source file size, modification time seconds and nanoseconds
+//! - string table: number of strings, then length and UTF-8 bytes of each
+//! - number of leases, then for each lease its IP address as a string table
+//!   index, a byte of flags telling which of the optional fields follow, the
+//!   dates as weekday, year, month, day, hour, minute and second, and the
+//!   hardware type, MAC address, uid, client hostname and hostname as string
+//!   table indices
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::common::Date;
+use crate::leases::{Hardware, Lease, LeaseDates, Leases, LeasesMethods};
+use crate::parser;
+
+const MAGIC: &[u8; 8] = b"DHCPDLS\0";
+
+/// Version of the snapshot format written by this crate
+pub const VERSION: u32 = 1;
+
+const HAS_STARTS: u8 = 1;
+const HAS_ENDS: u8 = 1 << 1;
+const HAS_HARDWARE: u8 = 1 << 2;
+const HAS_UID: u8 = 1 << 3;
+const HAS_CLIENT_HOSTNAME: u8 = 1 << 4;
+const HAS_HOSTNAME: u8 = 1 << 5;
+const ABANDONED: u8 = 1 << 6;
+
+/// Leases file a snapshot was made from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SourceInfo {
+    pub size: u64,
+    pub modified: SystemTime,
+}
+
+impl SourceInfo {
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<SourceInfo, String> {
+        let path = path.as_ref();
+        let meta = fs::metadata(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let modified = meta
+            .modified()
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+
+        Ok(SourceInfo {
+            size: meta.len(),
+            modified,
+        })
+    }
+}
+
+/// Writes a snapshot of `leases` made from the `source` file
+pub fn save<W: Write>(w: &mut W, leases: &Leases, source: &SourceInfo) -> Result<(), String> {
+    let mut strings = StringTable::default();
+    let mut body = Vec::new();
+
+    write_varint(&mut body, leases.len() as u64);
+    for l in leases.iter() {
+        write_varint(&mut body, strings.index(&l.ip));
+
+        let mut flags = 0;
+        let optional = [
+            (l.dates.starts.is_some(), HAS_STARTS),
+            (l.dates.ends.is_some(), HAS_ENDS),
+            (l.hardware.is_some(), HAS_HARDWARE),
+            (l.uid.is_some(), HAS_UID),
+            (l.client_hostname.is_some(), HAS_CLIENT_HOSTNAME),
+            (l.hostname.is_some(), HAS_HOSTNAME),
+            (l.abandoned, ABANDONED),
+        ];
+        for (present, flag) in optional.iter() {
+            if *present {
+                flags |= flag;
+            }
+        }
+        body.push(flags);
+
+        for d in [l.dates.starts, l.dates.ends].iter().flatten() {
+            write_date(&mut body, d);
+        }
+        if let Some(hw) = &l.hardware {
+            write_varint(&mut body, strings.index(&hw.h_type));
+            write_varint(&mut body, strings.index(&hw.mac));
+        }
+        for s in [&l.uid, &l.client_hostname, &l.hostname]
+            .iter()
+            .copied()
+            .flatten()
+        {
+            write_varint(&mut body, strings.index(s));
+        }
+    }
+
+    let mut header = Vec::new();
+    header.extend_from_slice(MAGIC);
+    header.extend_from_slice(&VERSION.to_le_bytes());
+    write_source_info(&mut header, source);
+
+    write_varint(&mut header, strings.strings.len() as u64);
+    for s in strings.strings.iter() {
+        write_varint(&mut header, s.len() as u64);
+        header.extend_from_slice(s.as_bytes());
+    }
+
+    w.write_all(&header)
+        .and_then(|_| w.write_all(&body))
+        .map_err(|e| e.to_string())
+}
+
+/// Reads only the header of a snapshot
+pub fn read_source_info<R: Read>(r: &mut R) -> Result<SourceInfo, String> {
+    let mut magic = [0; 8];
+    r.read_exact(&mut magic).map_err(|e| e.to_string())?;
+    if &magic != MAGIC {
+        return Err("Not a leases snapshot".to_owned());
+    }
+
+    let mut version = [0; 4];
+    r.read_exact(&mut version).map_err(|e| e.to_string())?;
+    let version = u32::from_le_bytes(version);
+    if version != VERSION {
+        return Err(format!(
+            "Unsupported snapshot version {}, expected {}",
+            version, VERSION
+        ));
+    }
+
+    let size = read_varint(r)?;
+    let secs = read_signed(r)?;
+    let nanos = read_varint(r)?;
+    if nanos >= 1_000_000_000 {
+        return Err(format!("Invalid modification time nanoseconds {}", nanos));
+    }
+    let since_epoch = Duration::new(secs.unsigned_abs(), nanos as u32);
+    let modified = if secs >= 0 {
+        UNIX_EPOCH + since_epoch
+    } else {
+        UNIX_EPOCH - since_epoch
+    };
+
+    Ok(SourceInfo { size, modified })
+}
+
+/// Reads a snapshot written by [`save`]
+pub fn load<R: Read>(r: &mut R) -> Result<(SourceInfo, Leases), String> {
+    let source = read_source_info(r)?;
+
+    Ok((source, read_leases(r)?))
+}
+
+/// Reads the part of the snapshot following the header
+fn read_leases<R: Read>(r: &mut R) -> Result<Leases, String> {
+    let count = read_varint(r)?;
+    let mut strings = Vec::new();
+    for _ in 0..count {
+        let len = read_varint(r)?;
+        let mut bytes = Vec::new();
+        r.take(len)
+            .read_to_end(&mut bytes)
+            .map_err(|e| e.to_string())?;
+        if bytes.len() as u64 != len {
+            return Err("Unexpected end of snapshot".to_owned());
+        }
+        strings.push(String::from_utf8(bytes).map_err(|e| e.to_string())?);
+    }
+    let string = |r: &mut R| -> Result<String, String> {
+        let i = read_varint(r)?;
+        strings
+            .get(i as usize)
+            .cloned()
+            .ok_or(format!("String index {} out of range", i))
+    };
+
+    let mut leases = Leases::new();
+    let count = read_varint(r)?;
+    for _ in 0..count {
+        let ip = string(r)?;
+        let flags = read_byte(r)?;
+        let has = |flag: u8| flags & flag != 0;
+
+        let starts = if has(HAS_STARTS) {
+            Some(read_date(r)?)
+        } else {
+            None
+        };
+        let ends = if has(HAS_ENDS) {
+            Some(read_date(r)?)
+        } else {
+            None
+        };
+        let hardware = if has(HAS_HARDWARE) {
+            Some(Hardware {
+                h_type: string(r)?,
+                mac: string(r)?,
+            })
+        } else {
+            None
+        };
+        let uid = if has(HAS_UID) { Some(string(r)?) } else { None };
+        let client_hostname = if has(HAS_CLIENT_HOSTNAME) {
+            Some(string(r)?)
+        } else {
+            None
+        };
+        let hostname = if has(HAS_HOSTNAME) {
+            Some(string(r)?)
+        } else {
+            None
+        };
+
+        leases.push(Lease {
+            ip,
+            dates: LeaseDates { starts, ends },
+            hardware,
+            uid,
+            client_hostname,
+            hostname,
+            abandoned: has(ABANDONED),
+        });
+    }
+
+    Ok(leases)
+}
+
+/// Loads leases of the `source` file from the snapshot at `cache` when it is
+/// up to date. Otherwise the source file is parsed and a new snapshot is
+/// written, failing to write it is not an error.
+pub fn load_cached<P: AsRef<Path>, Q: AsRef<Path>>(source: P, cache: Q) -> Result<Leases, String> {
+    let (source, cache) = (source.as_ref(), cache.as_ref());
+    let info = SourceInfo::from_path(source)?;
+
+    if let Ok(f) = fs::File::open(cache) {
+        let mut r = BufReader::new(f);
+        if read_source_info(&mut r) == Ok(info) {
+            if let Ok(leases) = read_leases(&mut r) {
+                return Ok(leases);
+            }
+        }
+    }
+
+    let leases = parser::parse_path(source)?.leases;
+    let _ = write_cache(cache, &leases, &info);
+
+    Ok(leases)
+}
+
+fn write_cache(cache: &Path, leases: &Leases, info: &SourceInfo) -> Result<(), String> {
+    let mut tmp = cache.as_os_str().to_owned();
+    tmp.push(format!(".{}", process::id()));
+    let tmp = PathBuf::from(tmp);
+
+    let res = fs::File::create(&tmp)
+        .map_err(|e| e.to_string())
+        .and_then(|f| {
+            let mut w = BufWriter::new(f);
+            save(&mut w, leases, info)?;
+            w.flush().map_err(|e| e.to_string())
+        })
+        .and_then(|_| fs::rename(&tmp, cache).map_err(|e| e.to_string()));
+    if res.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+
+    res
+}
+
+#[derive(Default)]
+struct StringTable<'a> {
+    strings: Vec<&'a str>,
+    indices: HashMap<&'a str, u64>,
+}
+
+impl<'a> StringTable<'a> {
+    fn index(&mut self, s: &'a str) -> u64 {
+        let strings = &mut self.strings;
+        *self.indices.entry(s).or_insert_with(|| {
+            strings.push(s);
+            strings.len() as u64 - 1
+        })
+    }
+}
+
+fn write_source_info(w: &mut Vec<u8>, source: &SourceInfo) {
+    let (secs, nanos) = match source.modified.duration_since(UNIX_EPOCH) {
+        Ok(d) => (d.as_secs() as i64, d.subsec_nanos()),
+        Err(e) => {
+            let d = e.duration();
+            (-(d.as_secs() as i64), d.subsec_nanos())
+        }
+    };
+
+    write_varint(w, source.size);
+    write_signed(w, secs);
+    write_varint(w, nanos as u64);
+}
+
+fn write_date(w: &mut Vec<u8>, d: &Date) {
+    for v in [
+        d.weekday, d.year, d.month, d.day, d.hour, d.minute, d.second,
+    ]
+    .iter()
+    {
+        write_signed(w, *v);
+    }
+}
+
+fn read_date<R: Read>(r: &mut R) -> Result<Date, String> {
+    Ok(Date {
+        weekday: read_signed(r)?,
+        year: read_signed(r)?,
+        month: read_signed(r)?,
+        day: read_signed(r)?,
+        hour: read_signed(r)?,
+        minute: read_signed(r)?,
+        second: read_signed(r)?,
+    })
+}
+
+fn write_varint(w: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        w.push(v as u8 | 0x80);
+        v >>= 7;
+    }
+    w.push(v as u8);
+}
+
+fn write_signed(w: &mut Vec<u8>, v: i64) {
+    write_varint(w, ((v << 1) ^ (v >> 63)) as u64);
+}
+
+fn read_byte<R: Read>(r: &mut R) -> Result<u8, String> {
+    let mut b = [0];
+    r.read_exact(&mut b)
+        .map_err(|_| "Unexpected end of snapshot".to_owned())?;
+
+    Ok(b[0])
+}
+
+fn read_varint<R: Read>(r: &mut R) -> Result<u64, String> {
+    let mut v = 0;
+    for shift in (0..64).step_by(7) {
+        let b = read_byte(r)?;
+        v |= u64::from(b & 0x7f) << shift;
+        if b & 0x80 == 0 {
+            return Ok(v);
+        }
+    }
+
+    Err("Invalid varint in snapshot".to_owned())
+}
+
+fn read_signed<R: Read>(r: &mut R) -> Result<i64, String> {
+    let v = read_varint(r)?;
+
+    Ok((v >> 1) as i64 ^ -((v & 1) as i64))
+}
@@ -0,0 +1,354 @@
+//! Statistics of a leases journal, computed as plain data for charts.
+//!
+//! Leases are held as in [`conflicts`](crate::conflicts), from their `starts`
+//! up to, not including, their `ends`, so that a renewal handing over at the
+//! same second is not counted twice. Leases without `starts` are left out of
+//! the timeline and leases without `ends` are held until
+//! [`StatsOptions::now`]. The timeline and the days have at most
+//! [`MAX_SAMPLES`] entries each, so that a single date far from the others
+//! cannot exhaust memory.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::Duration;
+
+use crate::common::Date;
+use crate::conflicts::held;
+use crate::leases::{ClientId, Leases};
+use crate::oui::OuiDb;
+use crate::sessions::{self, SessionOptions};
+
+const DAY: i64 = 86400;
+
+/// Highest number of entries of [`Stats::timeline`] and [`Stats::days`]
+pub const MAX_SAMPLES: i64 = 1_000_000;
+
+/// Upper bounds of the buckets of [`Stats::durations`], in seconds
+const DURATION_BOUNDS: [u64; 8] = [300, 1800, 3600, 7200, 21600, 43200, 86400, 604800];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StatsOptions {
+    /// Interval between the samples of [`Stats::timeline`]
+    pub resolution: Duration,
+    /// Number of entries of the top lists
+    pub top: usize,
+    pub now: Date,
+    /// Registry for [`Stats::top_vendors`]
+    pub oui: Option<OuiDb>,
+}
+
+impl Default for StatsOptions {
+    fn default() -> StatsOptions {
+        StatsOptions {
+            resolution: Duration::from_secs(3600),
+            top: 10,
+            now: Date::now(),
+            oui: None,
+        }
+    }
+}
+
+/// Number of active leases at a point in time
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Sample {
+    pub at: Date,
+    pub active: usize,
+}
+
+/// Number of leases lasting at least `min` and less than `max`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DurationBucket {
+    pub min: Duration,
+    /// `None` for the last bucket
+    pub max: Option<Duration>,
+    pub count: usize,
+}
+
+/// Clients with a lease starting on a day, by whether they were seen before
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DayClients {
+    /// Midnight UTC of the day
+    pub day: Date,
+    pub new: usize,
+    pub returning: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stats {
+    pub declarations: usize,
+    /// Distinct hardware addresses and uids
+    pub clients: usize,
+    /// Abandoned declarations
+    pub abandoned: usize,
+    /// Active leases sampled every [`StatsOptions::resolution`], from the
+    /// first `starts` rounded down to the resolution until the last change
+    /// or `now`, whichever is earlier
+    pub timeline: Vec<Sample>,
+    /// Highest number of leases active at once and when it was first reached
+    pub peak: Option<Sample>,
+    /// Declared lease durations, leases without `starts` or `ends` excluded
+    pub durations: Vec<DurationBucket>,
+    /// Declarations continuing a session of the same client and address,
+    /// see [`sessions`]
+    pub renewals: usize,
+    /// Share of the declarations of sessions which are renewals
+    pub renewal_rate: f64,
+    /// Every day from the first to the last `starts`
+    pub days: Vec<DayClients>,
+    /// Hostnames, client hostnames preferred, by number of declarations
+    pub top_hostnames: Vec<(String, usize)>,
+    /// First three octets of ethernet addresses by number of addresses
+    pub top_ouis: Vec<(String, usize)>,
+    /// Manufacturers of ethernet addresses by number of addresses, empty
+    /// without [`StatsOptions::oui`], unknown addresses left out
+    pub top_vendors: Vec<(String, usize)>,
+}
+
+/// Computes statistics of the leases journal. Fails if the timeline or the
+/// days would have more than [`MAX_SAMPLES`] entries.
+pub fn compute(leases: &Leases, options: &StatsOptions) -> Result<Stats, String> {
+    let now = options.now.timestamp();
+    let (timeline, peak) = timeline(leases, now, options.resolution)?;
+    let (renewals, renewal_rate) = renewals(leases, options.now);
+    let macs: HashSet<String> = leases
+        .iter()
+        .filter_map(|l| l.hardware.as_ref())
+        .filter(|hw| hw.h_type == "ethernet")
+        .map(|hw| hw.mac.to_lowercase())
+        .collect();
+
+    Ok(Stats {
+        declarations: leases.len(),
+        clients: leases
+            .iter()
+            .filter_map(|l| l.client_id())
+            .collect::<HashSet<ClientId>>()
+            .len(),
+        abandoned: leases.iter().filter(|l| l.abandoned).count(),
+        timeline,
+        peak,
+        durations: durations(leases),
+        renewals,
+        renewal_rate,
+        days: days(leases)?,
+        top_hostnames: top(
+            leases.iter().filter_map(|l| {
+                l.client_hostname
+                    .as_ref()
+                    .or(l.hostname.as_ref())
+                    .map(|hn| hn.to_lowercase())
+            }),
+            options.top,
+        ),
+        top_ouis: top(
+            macs.iter()
+                .filter_map(|mac| mac.get(..8).map(str::to_owned)),
+            options.top,
+        ),
+        top_vendors: match &options.oui {
+            Some(db) => top(
+                macs.iter()
+                    .filter_map(|mac| db.lookup(mac).map(str::to_owned)),
+                options.top,
+            ),
+            None => Vec::new(),
+        },
+    })
+}
+
+fn timeline(
+    leases: &Leases,
+    now: i64,
+    resolution: Duration,
+) -> Result<(Vec<Sample>, Option<Sample>), String> {
+    let events = events(leases, now);
+
+    let mut peak: Option<(i64, i64)> = None;
+    let mut active = 0;
+    for (n, &(ts, delta)) in events.iter().enumerate() {
+        active += delta;
+        let last_of_second = events.get(n + 1).is_none_or(|e| e.0 != ts);
+        if last_of_second && peak.is_none_or(|(_, max)| active > max) {
+            peak = Some((ts, active));
+        }
+    }
+
+    let step = resolution.as_secs().max(1) as i64;
+    let samples = match (events.first(), events.last()) {
+        (Some(first), Some(last)) => {
+            sample(&events, first.0.div_euclid(step) * step, last.0, step)?
+        }
+        _ => Vec::new(),
+    };
+
+    let peak = peak.map(|(at, active)| Sample {
+        at: Date::from_timestamp(at),
+        active: active as usize,
+    });
+
+    Ok((samples, peak))
+}
+
+/// Returns the number of active leases every `step` seconds from `from` up
+/// to, not including, `to`
+pub(crate) fn active_samples(
+    leases: &Leases,
+    from: i64,
+    to: i64,
+    step: i64,
+) -> Result<Vec<Sample>, String> {
+    sample(&events(leases, to), from, to - 1, step)
+}
+
+/// Returns starts and ends of the held leases, ends clamped to `now` and
+/// sorted before starts of the same second
+fn events(leases: &Leases, now: i64) -> Vec<(i64, i64)> {
+    let mut events = Vec::new();
+    for (_, _, start, end) in held(leases) {
+        let end = end.min(now);
+        if start != i64::MIN && start < end {
+            events.push((start, 1));
+            events.push((end, -1));
+        }
+    }
+    events.sort_unstable();
+
+    events
+}
+
+/// Samples the number of active leases every `step` seconds from `from` up to
+/// and including `to`
+fn sample(events: &[(i64, i64)], from: i64, to: i64, step: i64) -> Result<Vec<Sample>, String> {
+    if to.saturating_sub(from) / step >= MAX_SAMPLES {
+        return Err(format!(
+            "timeline would have more than {} samples, use a coarser resolution",
+            MAX_SAMPLES
+        ));
+    }
+
+    let mut samples = Vec::new();
+    let (mut at, mut active, mut n) = (from, 0, 0);
+    while at <= to {
+        while n < events.len() && events[n].0 <= at {
+            active += events[n].1;
+            n += 1;
+        }
+        samples.push(Sample {
+            at: Date::from_timestamp(at),
+            active: active as usize,
+        });
+        at += step;
+    }
+
+    Ok(samples)
+}
+
+fn durations(leases: &Leases) -> Vec<DurationBucket> {
+    let mut buckets: Vec<DurationBucket> = Vec::new();
+    let mut min = 0;
+    for &max in DURATION_BOUNDS.iter() {
+        buckets.push(bucket(min, Some(max)));
+        min = max;
+    }
+    buckets.push(bucket(min, None));
+
+    for l in leases.iter() {
+        if let (Some(starts), Some(ends)) = (l.dates.starts, l.dates.ends) {
+            let secs = ends.timestamp() - starts.timestamp();
+            if secs < 0 {
+                continue;
+            }
+            let n = DURATION_BOUNDS
+                .iter()
+                .take_while(|&&max| secs as u64 >= max)
+                .count();
+            buckets[n].count += 1;
+        }
+    }
+
+    buckets
+}
+
+fn bucket(min: u64, max: Option<u64>) -> DurationBucket {
+    DurationBucket {
+        min: Duration::from_secs(min),
+        max: max.map(Duration::from_secs),
+        count: 0,
+    }
+}
+
+fn renewals(leases: &Leases, now: Date) -> (usize, f64) {
+    let options = SessionOptions {
+        now,
+        ..SessionOptions::default()
+    };
+
+    let (mut renewals, mut declarations) = (0, 0);
+    for device in sessions::devices(leases, &options) {
+        for s in device.sessions.iter() {
+            renewals += s.leases.len() - 1;
+            declarations += s.leases.len();
+        }
+    }
+
+    if declarations == 0 {
+        return (0, 0.0);
+    }
+
+    (renewals, renewals as f64 / declarations as f64)
+}
+
+fn days(leases: &Leases) -> Result<Vec<DayClients>, String> {
+    let mut seen: BTreeMap<i64, HashSet<ClientId>> = BTreeMap::new();
+    for l in leases.iter() {
+        if let (Some(starts), Some(client)) = (l.dates.starts, l.client_id()) {
+            seen.entry(starts.timestamp().div_euclid(DAY))
+                .or_default()
+                .insert(client);
+        }
+    }
+
+    let (first, last) = match (seen.keys().next(), seen.keys().next_back()) {
+        (Some(&first), Some(&last)) => (first, last),
+        _ => return Ok(Vec::new()),
+    };
+    if last - first >= MAX_SAMPLES {
+        return Err(format!(
+            "leases start over more than {} days",
+            MAX_SAMPLES
+        ));
+    }
+
+    let mut known: HashSet<&ClientId> = HashSet::new();
+    let mut res = Vec::new();
+    for day in first..=last {
+        let mut entry = DayClients {
+            day: Date::from_timestamp(day * DAY),
+            new: 0,
+            returning: 0,
+        };
+        for client in seen.get(&day).into_iter().flatten() {
+            if known.insert(client) {
+                entry.new += 1;
+            } else {
+                entry.returning += 1;
+            }
+        }
+        res.push(entry);
+    }
+
+    Ok(res)
+}
+
+/// Returns the `n` most frequent values, ties ordered by value
+fn top<I: Iterator<Item = String>>(values: I, n: usize) -> Vec<(String, usize)> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for v in values {
+        *counts.entry(v).or_default() += 1;
+    }
+
+    let mut res: Vec<(String, usize)> = counts.into_iter().collect();
+    res.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    res.truncate(n);
+
+    res
+}
@@ -0,0 +1,171 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex, RwLock};
+
+#[cfg(feature = "watch")]
+use std::thread;
+#[cfg(feature = "watch")]
+use std::time::Duration;
+
+use crate::leases::Leases;
+use crate::parser;
+
+/// Notification sent to subscribers of a [`LeaseStore`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReloadEvent {
+    /// The leases file was parsed and the new snapshot is being served
+    Reloaded(Arc<Leases>),
+    /// The leases file could not be parsed, the previous snapshot is still
+    /// being served
+    Failed(String),
+}
+
+#[derive(Debug)]
+struct Shared {
+    path: PathBuf,
+    snapshot: RwLock<Arc<Leases>>,
+    subscribers: Mutex<Vec<mpsc::Sender<ReloadEvent>>>,
+}
+
+impl Shared {
+    fn reload(&self) -> Result<Arc<Leases>, String> {
+        let res = fs::read_to_string(&self.path)
+            .map_err(|e| format!("{}: {}", self.path.display(), e))
+            .and_then(parser::parse)
+            .map(|r| Arc::new(r.leases));
+
+        let event = match &res {
+            Ok(leases) => {
+                *self.snapshot.write().unwrap() = leases.clone();
+                ReloadEvent::Reloaded(leases.clone())
+            }
+            Err(e) => ReloadEvent::Failed(e.clone()),
+        };
+        self.subscribers
+            .lock()
+            .unwrap()
+            .retain(|tx| tx.send(event.clone()).is_ok());
+
+        res
+    }
+}
+
+/// Shared, reloadable snapshot of a leases file.
+///
+/// Readers get the current snapshot as an `Arc<Leases>` and keep using it for
+/// as long as they want, a reload only swaps the pointer for the following
+/// readers. The file is parsed before taking the lock, so getting the snapshot
+/// never waits for a reload to parse.
+/// When the file cannot be parsed the last good snapshot stays in place.
+///
+/// With the `watch` feature enabled, [`LeaseStore::watch`] reloads the store
+/// automatically whenever the leases file changes.
+#[derive(Debug)]
+pub struct LeaseStore {
+    shared: Arc<Shared>,
+    #[cfg(feature = "watch")]
+    _watcher: Option<notify::RecommendedWatcher>,
+}
+
+impl LeaseStore {
+    /// Loads the leases file at `path`, which has to parse successfully
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<LeaseStore, String> {
+        let path = path.as_ref().to_path_buf();
+        let input = fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let leases = parser::parse(input)?.leases;
+
+        Ok(LeaseStore {
+            shared: Arc::new(Shared {
+                path,
+                snapshot: RwLock::new(Arc::new(leases)),
+                subscribers: Mutex::new(Vec::new()),
+            }),
+            #[cfg(feature = "watch")]
+            _watcher: None,
+        })
+    }
+
+    /// Loads the leases file at `path` and reloads it whenever it changes.
+    ///
+    /// Bursts of changes are coalesced, the file is reloaded once no change
+    /// arrived for `debounce`. The directory of the file is watched rather
+    /// than the file itself, so the store keeps up with dhcpd replacing the
+    /// file when it rewrites it.
+    #[cfg(feature = "watch")]
+    pub fn watch<P: AsRef<Path>>(path: P, debounce: Duration) -> Result<LeaseStore, String> {
+        use notify::{EventKind, RecursiveMode, Watcher};
+
+        let mut store = LeaseStore::open(path)?;
+
+        let path = store.shared.path.clone();
+        let dir = match path.parent() {
+            Some(d) if !d.as_os_str().is_empty() => d.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        let name = path.file_name().map(|n| n.to_owned());
+
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(tx).map_err(|e| e.to_string())?;
+        watcher
+            .watch(&dir, RecursiveMode::NonRecursive)
+            .map_err(|e| format!("{}: {}", dir.display(), e))?;
+
+        let is_relevant = move |res: notify::Result<notify::Event>| match res {
+            Ok(ev) => {
+                matches!(
+                    ev.kind,
+                    EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+                ) && ev.paths.iter().any(|p| p.file_name() == name.as_deref())
+            }
+            Err(_) => false,
+        };
+
+        let shared = store.shared.clone();
+        thread::spawn(move || {
+            // the channel disconnects once the store, and with it the
+            // watcher, is dropped
+            while let Ok(res) = rx.recv() {
+                if !is_relevant(res) {
+                    continue;
+                }
+
+                loop {
+                    match rx.recv_timeout(debounce) {
+                        Ok(_) => continue,
+                        Err(mpsc::RecvTimeoutError::Timeout) => break,
+                        Err(mpsc::RecvTimeoutError::Disconnected) => return,
+                    }
+                }
+
+                let _ = shared.reload();
+            }
+        });
+
+        store._watcher = Some(watcher);
+
+        Ok(store)
+    }
+
+    /// Returns the current snapshot
+    pub fn snapshot(&self) -> Arc<Leases> {
+        self.shared.snapshot.read().unwrap().clone()
+    }
+
+    /// Parses the leases file again and replaces the snapshot on success
+    pub fn reload(&self) -> Result<Arc<Leases>, String> {
+        self.shared.reload()
+    }
+
+    /// Returns a channel receiving an event after every reload
+    pub fn subscribe(&self) -> mpsc::Receiver<ReloadEvent> {
+        let (tx, rx) = mpsc::channel();
+        self.shared.subscribers.lock().unwrap().push(tx);
+
+        rx
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.shared.path
+    }
+}
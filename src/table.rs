@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+
+use crate::leases::Lease;
+use crate::leases::Leases;
+use crate::leases::LeasesMethods;
+
+/// Current state of a leases journal.
+///
+/// dhcpd never rewrites a lease in place, it appends a new declaration for the
+/// same address every time the lease changes. When the file is loaded the last
+/// declaration for an address wins. `LeaseTable` applies the same rule and
+/// keeps the superseded declarations around as history.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LeaseTable {
+    current: Leases,
+    positions: HashMap<String, usize>,
+    superseded: HashMap<String, Vec<Lease>>,
+}
+
+impl LeaseTable {
+    /// Collapses `leases` to a single lease per IP address.
+    ///
+    /// Current leases keep the order in which their last declaration appears
+    /// in the journal.
+    pub fn from_leases(leases: &Leases) -> LeaseTable {
+        let mut last = HashMap::new();
+        for (i, l) in leases.iter().enumerate() {
+            last.insert(l.ip.as_str(), i);
+        }
+
+        let mut current = Leases::new();
+        let mut positions = HashMap::new();
+        let mut superseded: HashMap<String, Vec<Lease>> = HashMap::new();
+
+        for (i, l) in leases.iter().enumerate() {
+            if last[l.ip.as_str()] == i {
+                positions.insert(l.ip.clone(), current.len());
+                current.push(l.clone());
+            } else {
+                superseded.entry(l.ip.clone()).or_default().push(l.clone());
+            }
+        }
+
+        LeaseTable {
+            current,
+            positions,
+            superseded,
+        }
+    }
+
+    /// Returns the current lease for `ip`.
+    pub fn get<S: AsRef<str>>(&self, ip: S) -> Option<&Lease> {
+        self.positions.get(ip.as_ref()).map(|&i| &self.current[i])
+    }
+
+    /// Returns the current leases, one per IP address.
+    pub fn leases(&self) -> &Leases {
+        &self.current
+    }
+
+    pub fn into_leases(self) -> Leases {
+        self.current
+    }
+
+    /// Returns declarations for `ip` that were replaced by a later one, oldest
+    /// first.
+    pub fn superseded<S: AsRef<str>>(&self, ip: S) -> &[Lease] {
+        match self.superseded.get(ip.as_ref()) {
+            Some(ls) => ls,
+            None => &[],
+        }
+    }
+
+    /// Returns every declaration for `ip` in journal order, the current lease
+    /// being the last one.
+    pub fn history<S: AsRef<str>>(&self, ip: S) -> Vec<Lease> {
+        let mut res = self.superseded(ip.as_ref()).to_vec();
+        if let Some(l) = self.get(ip) {
+            res.push(l.clone());
+        }
+
+        res
+    }
+
+    /// Returns the number of declarations that were replaced by a later one.
+    pub fn superseded_count(&self) -> usize {
+        self.superseded.values().map(|ls| ls.len()).sum()
+    }
+
+    pub fn len(&self) -> usize {
+        self.current.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.current.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Lease> {
+        self.current.iter()
+    }
+}
@@ -0,0 +1,313 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::common::Date;
+use crate::leases::Lease;
+use crate::parser::ParserResult;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// Kind of a [`Finding`]. Codes are stable, new checks get new codes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Code {
+    /// `ends` is before `starts`
+    EndsBeforeStarts,
+    /// `starts` or `ends` is further in the future than
+    /// [`ValidateOptions::max_future_days`]
+    FarFuture,
+    /// The weekday doesn't match the date
+    WeekdayMismatch,
+    /// The hardware address is not a list of hexadecimal octets
+    MalformedMac,
+    /// The uid is neither a list of hexadecimal octets nor a quoted string
+    MalformedUid,
+    /// Another client holds the same IP address at the same time
+    OverlappingLease,
+    /// The lease is abandoned but has not expired yet
+    AbandonedInWindow,
+    /// The lease has no `hardware` statement
+    MissingHardware,
+    /// `starts` or `ends` is outside of the range of [`Date::from`], the
+    /// other date checks are skipped
+    DateOutOfRange,
+}
+
+impl Code {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Code::EndsBeforeStarts => "L001",
+            Code::FarFuture => "L002",
+            Code::WeekdayMismatch => "L003",
+            Code::MalformedMac => "L004",
+            Code::MalformedUid => "L005",
+            Code::OverlappingLease => "L006",
+            Code::AbandonedInWindow => "L007",
+            Code::MissingHardware => "L008",
+            Code::DateOutOfRange => "L009",
+        }
+    }
+
+    pub fn severity(&self) -> Severity {
+        match self {
+            Code::EndsBeforeStarts
+            | Code::MalformedMac
+            | Code::OverlappingLease
+            | Code::DateOutOfRange => Severity::Error,
+            Code::FarFuture
+            | Code::WeekdayMismatch
+            | Code::MalformedUid
+            | Code::AbandonedInWindow
+            | Code::MissingHardware => Severity::Warning,
+        }
+    }
+}
+
+impl fmt::Display for Code {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Problem of a single lease
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Finding {
+    pub code: Code,
+    /// Index of the lease in [`ParserResult::leases`]
+    pub lease: usize,
+    pub ip: String,
+    pub message: String,
+}
+
+impl Finding {
+    pub fn severity(&self) -> Severity {
+        self.code.severity()
+    }
+}
+
+/// Formats the finding like `L001 error 10.0.0.2: ends before starts`
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {}: {}",
+            self.code,
+            self.severity(),
+            self.ip,
+            self.message
+        )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidateOptions {
+    /// Date abandoned leases and future dates are checked against
+    pub now: Date,
+    pub max_future_days: i64,
+}
+
+impl Default for ValidateOptions {
+    fn default() -> ValidateOptions {
+        ValidateOptions {
+            now: Date::now(),
+            max_future_days: 365,
+        }
+    }
+}
+
+/// Checks the leases with the default options, see [`validate_with`]
+pub fn validate(res: &ParserResult) -> Vec<Finding> {
+    validate_with(res, &ValidateOptions::default())
+}
+
+/// Returns findings ordered by lease and code
+pub fn validate_with(res: &ParserResult, options: &ValidateOptions) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let limit = options
+        .now
+        .timestamp()
+        .saturating_add(options.max_future_days.saturating_mul(86400));
+
+    for (i, l) in res.leases.iter().enumerate() {
+        let mut found = |code: Code, message: String| {
+            findings.push(Finding {
+                code,
+                lease: i,
+                ip: l.ip.clone(),
+                message,
+            })
+        };
+
+        let mut dates_valid = true;
+        for (name, date) in [("starts", l.dates.starts), ("ends", l.dates.ends)].iter() {
+            if let Some(d) = date.filter(|d| d.checked_timestamp().is_none()) {
+                dates_valid = false;
+                found(
+                    Code::DateOutOfRange,
+                    format!("{} {} is out of range", name, d.to_leases_format()),
+                );
+            }
+        }
+
+        if let (Some(starts), Some(ends)) = (l.dates.starts, l.dates.ends) {
+            if dates_valid && ends < starts {
+                found(
+                    Code::EndsBeforeStarts,
+                    format!(
+                        "ends {} before starts {}",
+                        ends.to_rfc3339(),
+                        starts.to_rfc3339()
+                    ),
+                );
+            }
+        }
+
+        for (name, date) in [("starts", l.dates.starts), ("ends", l.dates.ends)].iter() {
+            if let Some(d) = date.filter(|_| dates_valid) {
+                if d.timestamp() > limit {
+                    found(
+                        Code::FarFuture,
+                        format!(
+                            "{} {} is more than {} days ahead",
+                            name,
+                            d.to_rfc3339(),
+                            options.max_future_days
+                        ),
+                    );
+                }
+            }
+        }
+
+        for (name, date) in [("starts", l.dates.starts), ("ends", l.dates.ends)].iter() {
+            if let Some(d) = date.filter(|_| dates_valid) {
+                let expected = Date::from_timestamp(d.timestamp()).weekday;
+                if d.weekday != expected {
+                    found(
+                        Code::WeekdayMismatch,
+                        format!(
+                            "{} {} is on weekday {}, not {}",
+                            name,
+                            d.to_rfc3339(),
+                            expected,
+                            d.weekday
+                        ),
+                    );
+                }
+            }
+        }
+
+        if let Some(hw) = &l.hardware {
+            let octets = hex_octets(&hw.mac);
+            let valid = match octets {
+                Some(n) if hw.h_type == "ethernet" => n == 6,
+                Some(_) => true,
+                None => false,
+            };
+            if !valid {
+                found(
+                    Code::MalformedMac,
+                    format!("malformed {} address {}", hw.h_type, hw.mac),
+                );
+            }
+        }
+
+        if let Some(uid) = &l.uid {
+            let quoted = uid.len() >= 2 && uid.starts_with('"') && uid.ends_with('"');
+            if !quoted && hex_octets(uid).is_none() {
+                found(Code::MalformedUid, format!("malformed uid {}", uid));
+            }
+        }
+
+        if dates_valid && l.abandoned && l.is_active_at(options.now) {
+            found(
+                Code::AbandonedInWindow,
+                "abandoned lease has not expired yet".to_owned(),
+            );
+        }
+
+        if l.hardware.is_none() {
+            found(Code::MissingHardware, "no hardware address".to_owned());
+        }
+    }
+
+    findings.extend(overlapping(res));
+    findings.sort_by_key(|f| (f.lease, f.code));
+
+    findings
+}
+
+/// Returns the number of octets of a colon separated hexadecimal string
+fn hex_octets(s: &str) -> Option<usize> {
+    let mut n = 0;
+    for octet in s.split(':') {
+        if octet.is_empty() || octet.len() > 2 || !octet.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        n += 1;
+    }
+
+    Some(n)
+}
+
+/// Reports later of two leases of the same IP address held by different
+/// clients during a common, non-empty period
+fn overlapping(res: &ParserResult) -> Vec<Finding> {
+    let mut by_ip: HashMap<&str, Vec<(usize, &Lease)>> = HashMap::new();
+    for (i, l) in res.leases.iter().enumerate() {
+        let dates_valid = [l.dates.starts, l.dates.ends]
+            .iter()
+            .flatten()
+            .all(|d| d.checked_timestamp().is_some());
+        if dates_valid && !l.abandoned && l.client_id().is_some() {
+            by_ip.entry(&l.ip).or_default().push((i, l));
+        }
+    }
+
+    let mut findings = Vec::new();
+    for leases in by_ip.values_mut() {
+        leases.sort_by_key(|(i, l)| (l.dates.starts, *i));
+
+        for (a, &(_, first)) in leases.iter().enumerate() {
+            for &(j, second) in leases[a + 1..].iter() {
+                let first_ends = first.dates.ends.map(|d| d.timestamp()).unwrap_or(i64::MAX);
+                let second_starts = second
+                    .dates
+                    .starts
+                    .map(|d| d.timestamp())
+                    .unwrap_or(i64::MIN);
+                if second_starts >= first_ends {
+                    break;
+                }
+                let second_ends = second.dates.ends.map(|d| d.timestamp()).unwrap_or(i64::MAX);
+                if second_ends <= second_starts || first.client_id() == second.client_id() {
+                    continue;
+                }
+
+                findings.push(Finding {
+                    code: Code::OverlappingLease,
+                    lease: j,
+                    ip: second.ip.clone(),
+                    message: format!(
+                        "held by {} while also held by {}",
+                        second.client_id().unwrap(),
+                        first.client_id().unwrap()
+                    ),
+                });
+            }
+        }
+    }
+
+    findings
+}
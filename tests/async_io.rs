@@ -0,0 +1,88 @@
+#![cfg(feature = "tokio")]
+
+extern crate dhcpd_parser;
+
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::pin::Pin;
+use std::process;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+
+use crate::dhcpd_parser::async_io::{AsyncLeaseFollower, LeaseStream};
+use crate::dhcpd_parser::parser;
+
+const LEASES: &str = "
+    lease 10.0.0.2 {
+        starts 2 2019/01/01 10:00:00;
+        hardware ethernet 11:11:11:11:11:11;
+    }
+
+    lease 10.0.0.3 {
+        starts 2 2019/01/01 11:00:00;
+        hardware ethernet 22:22:22:22:22:22;
+    }
+";
+
+async fn next<S: Stream + Unpin>(s: &mut S) -> Option<S::Item> {
+    std::future::poll_fn(|cx: &mut Context<'_>| Pin::new(&mut *s).poll_next(cx)).await
+}
+
+#[tokio::test]
+async fn parse_async_test() {
+    let res = parser::parse_async(LEASES.as_bytes()).await.unwrap();
+    assert_eq!(res, parser::parse(LEASES).unwrap());
+}
+
+#[tokio::test]
+async fn lease_stream_test() {
+    let mut stream = LeaseStream::new(LEASES.as_bytes());
+
+    assert_eq!(next(&mut stream).await.unwrap().unwrap().ip, "10.0.0.2");
+    assert_eq!(next(&mut stream).await.unwrap().unwrap().ip, "10.0.0.3");
+    assert!(next(&mut stream).await.is_none());
+
+    let mut stream = LeaseStream::new(&LEASES.as_bytes()[..LEASES.len() - 10]);
+    assert!(next(&mut stream).await.unwrap().is_ok());
+    assert!(next(&mut stream).await.unwrap().is_err());
+    assert!(next(&mut stream).await.is_none());
+
+    // the stream is pending rather than finished while the reader waits
+    let (mut tx, rx) = tokio::io::duplex(64);
+    let mut stream = LeaseStream::new(rx);
+    let waker = std::task::Waker::noop();
+    let mut cx = Context::from_waker(waker);
+    assert!(Pin::new(&mut stream).poll_next(&mut cx) == Poll::Pending);
+
+    tokio::io::AsyncWriteExt::write_all(&mut tx, b"lease 10.0.0.4 { }").await.unwrap();
+    drop(tx);
+    assert_eq!(next(&mut stream).await.unwrap().unwrap().ip, "10.0.0.4");
+}
+
+#[tokio::test]
+async fn async_follow_test() {
+    let dir = env::temp_dir().join(format!("dhcpd-parser-async-follow-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("dhcpd.leases");
+    fs::write(&path, &LEASES.as_bytes()[..LEASES.len() - 10]).unwrap();
+
+    let mut follower = AsyncLeaseFollower::open(&path).await.unwrap();
+    assert_eq!(follower.leases().len(), 1);
+    assert!(follower.poll().await.unwrap().leases.is_empty());
+
+    let mut f = OpenOptions::new().append(true).open(&path).unwrap();
+    f.write_all(&LEASES.as_bytes()[LEASES.len() - 10..]).unwrap();
+    drop(f);
+
+    let batch = follower
+        .wait(std::time::Duration::from_millis(10))
+        .await
+        .unwrap();
+    assert!(!batch.resynced);
+    assert_eq!(batch.leases.len(), 1);
+    assert_eq!(batch.leases[0].ip, "10.0.0.3");
+
+    fs::remove_dir_all(&dir).unwrap();
+}
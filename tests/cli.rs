@@ -0,0 +1,223 @@
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::process::{self, Command, Output, Stdio};
+
+const LEASES: &str = "
+lease 10.0.0.2 {
+    starts 2 2019/01/01 10:00:00;
+    ends 2 2019/01/01 12:00:00;
+    hardware ethernet 11:11:11:11:11:11;
+    client-hostname \"laptop\";
+}
+lease 10.0.1.3 {
+    starts 2 2019/01/01 10:00:00;
+    abandoned;
+}
+lease 10.0.0.2 {
+    starts 2 2019/01/01 11:00:00;
+    ends 2 2019/01/01 13:00:00;
+    hardware ethernet 11:11:11:11:11:11;
+}
+";
+
+fn run(name: &str, args: &[&str]) -> Output {
+    run_on(name, LEASES, args)
+}
+
+fn run_on(name: &str, input: &str, args: &[&str]) -> Output {
+    let path = env::temp_dir().join(format!("dhcpd-parser-cli-{}-{}", name, process::id()));
+    fs::write(&path, input).unwrap();
+
+    let out = Command::new(env!("CARGO_BIN_EXE_dhcpd-leases"))
+        .args(args)
+        .arg(&path)
+        .output()
+        .unwrap();
+    fs::remove_file(&path).unwrap();
+
+    out
+}
+
+fn stdout(out: &Output) -> String {
+    assert!(out.status.success(), "{:?}", out);
+    String::from_utf8(out.stdout.clone()).unwrap()
+}
+
+#[test]
+fn list_test() {
+    let out = run(
+        "list",
+        &[
+            "list",
+            "--subnet",
+            "10.0.0.0/24",
+            "--active-at",
+            "2019-01-01T11:30:00Z",
+            "--template",
+            "{ip} {client-hostname} {state}",
+        ],
+    );
+    assert_eq!(stdout(&out), "10.0.0.2 laptop active\n10.0.0.2  active\n");
+
+    let out = run_on(
+        "template-braces",
+        "lease 10.0.0.4 { hardware ethernet 44:44:44:44:44:44; client-hostname \"{mac}\"; }",
+        &["list", "--template", "{client-hostname} {mac}"],
+    );
+    assert_eq!(stdout(&out), "{mac} 44:44:44:44:44:44\n");
+
+    let out = run("table", &["list", "--ip", "10.0.1.3"]);
+    assert_eq!(
+        stdout(&out),
+        "IP        MAC  HOSTNAME  STARTS                ENDS  STATE\n\
+         10.0.1.3  -    -         2019-01-01T10:00:00Z  -     abandoned\n"
+    );
+}
+
+#[test]
+fn where_test() {
+    let out = run(
+        "where",
+        &[
+            "list",
+            "--where",
+            "not abandoned and ip in 10.0.0.0/24",
+            "--current",
+            "--template",
+            "{ip}",
+        ],
+    );
+    assert_eq!(stdout(&out), "10.0.0.2\n");
+
+    let out = run("where-invalid", &["list", "--where", "ip ~"]);
+    assert_eq!(out.status.code(), Some(2));
+}
+
+#[test]
+fn show_test() {
+    let out = run(
+        "show",
+        &[
+            "show",
+            "11:11:11:11:11:11",
+            "--output",
+            "json",
+            "--columns",
+            "ip,starts",
+        ],
+    );
+    assert_eq!(
+        stdout(&out),
+        "[\n  {\"ip\":\"10.0.0.2\",\"dates\":{\"starts\":\"2019-01-01T10:00:00Z\"}},\n  \
+         {\"ip\":\"10.0.0.2\",\"dates\":{\"starts\":\"2019-01-01T11:00:00Z\"}}\n]\n"
+    );
+
+    let out = run("show-missing", &["show", "10.9.9.9"]);
+    assert_eq!(out.status.code(), Some(1));
+}
+
+#[test]
+fn export_test() {
+    let out = run(
+        "export",
+        &[
+            "export",
+            "--format",
+            "csv",
+            "--columns",
+            "ip,mac",
+            "--current",
+        ],
+    );
+    assert_eq!(
+        stdout(&out),
+        "ip,mac\r\n10.0.1.3,\r\n10.0.0.2,11:11:11:11:11:11\r\n"
+    );
+}
+
+#[test]
+fn stats_test() {
+    let out = run("stats", &["stats", "--resolution", "1h"]);
+    let stdout = stdout(&out);
+    assert!(stdout.starts_with("declarations  3\n"));
+    assert!(stdout.contains("renewals      1 (50.0%)\n"));
+    assert!(stdout.contains(
+        "Active leases\n\
+         2019-01-01T10:00:00Z  1\n\
+         2019-01-01T11:00:00Z  1\n\
+         2019-01-01T12:00:00Z  1\n\
+         2019-01-01T13:00:00Z  0\n"
+    ));
+    assert!(stdout.ends_with("Top OUIs\n11:11:11  1\n"));
+
+    let out = run("stats-usage", &["stats", "--resolution", "0h"]);
+    assert_eq!(out.status.code(), Some(2));
+
+    let out = run(
+        "stats-overflow",
+        &["stats", "--resolution", "1000000000000000000d"],
+    );
+    assert_eq!(out.status.code(), Some(2));
+}
+
+#[test]
+fn validate_test() {
+    let out = run("validate", &["validate"]);
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    assert!(out.status.success(), "{}", stdout);
+    assert!(stdout.contains(":8: L008 warning 10.0.1.3: no hardware address\n"));
+    assert!(stdout.ends_with("0 errors, 2 warnings\n"));
+
+    let out = run("validate-strict", &["validate", "--strict"]);
+    assert_eq!(out.status.code(), Some(1));
+
+    let absurd = "lease 10.0.0.2 { ends 4 99999999999999/01/01 00:00:00; }";
+    for cmd in ["validate", "list"].iter() {
+        let out = run_on(&format!("{}-year", cmd), absurd, &[cmd]);
+        assert_eq!(out.status.code(), Some(1), "{:?}", out);
+    }
+}
+
+#[test]
+fn validate_stdin_test() {
+    // a copy of the same declaration, out of order
+    let input = "lease 10.0.0.3 { starts 2 2019/01/01 11:00:00; }
+lease 10.0.0.2 { starts 2 2019/01/01 10:00:00; }
+lease 10.0.0.3 { starts 2 2019/01/01 11:00:00; }
+";
+    let from_file = stdout(&run_on("validate-file", input, &["validate"]));
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_dhcpd-leases"))
+        .args(["validate", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let from_stdin = stdout(&child.wait_with_output().unwrap());
+
+    let lines = |out: &str| -> Vec<String> {
+        out.lines()
+            .map(|l| l.split_once(':').map_or(l, |(_, rest)| rest).to_owned())
+            .collect()
+    };
+    assert_eq!(lines(&from_file), lines(&from_stdin));
+    assert!(from_stdin.starts_with("<stdin>:1: L008 warning 10.0.0.3"));
+    assert!(from_stdin.contains("<stdin>:3: L008 warning 10.0.0.3"));
+    assert!(from_stdin.ends_with("0 errors, 3 warnings\n"));
+}
+
+#[test]
+fn usage_error_test() {
+    let out = run("usage", &["list", "--subnet", "10.0.0.0/40"]);
+    assert_eq!(out.status.code(), Some(2));
+
+    let out = run("template", &["list", "--template", "{vendor}"]);
+    assert_eq!(out.status.code(), Some(2));
+}
@@ -39,3 +39,55 @@ fn date_rfc3339() {
         "This doesn\'t seem like a correct RFC3339 date: \"T\"",
     );
 }
+
+#[test]
+fn date_timestamp() {
+    let d = Date::from("2", "2019/01/01", "22:00:00").unwrap();
+    assert_eq!(d.timestamp(), 1546380000);
+    assert_eq!(Date::from_timestamp(1546380000), d);
+
+    assert_eq!(
+        Date::from_timestamp(0),
+        Date {
+            weekday: 4,
+            ..Date::new()
+        }
+    );
+    assert_eq!(
+        Date::from_timestamp(951782400).to_string(),
+        "Tuesday 2000/02/29 00:00:00"
+    );
+    assert_eq!(Date::from_timestamp(-1).to_leases_format(), "3 1969/12/31 23:59:59");
+}
+
+#[test]
+fn date_eq_ignores_weekday() {
+    use std::collections::HashSet;
+
+    let a = Date::from("0", "2019/01/01", "10:00:00").unwrap();
+    let b = Date::from("2", "2019/01/01", "10:00:00").unwrap();
+    assert_eq!(a.cmp(&b), std::cmp::Ordering::Equal);
+    assert_eq!(a, b);
+
+    let set: HashSet<Date> = [a, b].iter().cloned().collect();
+    assert_eq!(set.len(), 1);
+}
+
+#[test]
+fn date_range_test() {
+    assert!(Date::from("4", "99999999999999/01/01", "00:00:00").is_err());
+    assert!(Date::from("4", "10000/01/01", "00:00:00").is_err());
+    assert!(Date::from("4", "2019/13/01", "00:00:00").is_err());
+    assert!(Date::from("4", "2019/9223372036854775807/01", "00:00:00").is_err());
+    assert!(Date::from("4", "2019/01/32", "00:00:00").is_err());
+    assert!(Date::from_rfc3339(0, "99999-01-01T00:00:00Z").is_err());
+
+    let last = Date::from("5", "9999/12/31", "23:59:59").unwrap();
+    assert_eq!(last.checked_timestamp(), Some(253402300799));
+
+    let absurd = Date {
+        year: 99999999999999,
+        ..Date::new()
+    };
+    assert_eq!(absurd.checked_timestamp(), None);
+}
@@ -0,0 +1,84 @@
+extern crate dhcpd_parser;
+
+use std::env;
+use std::fs;
+use std::process;
+
+use crate::dhcpd_parser::common::Date;
+use crate::dhcpd_parser::compact;
+use crate::dhcpd_parser::compact::CompactOptions;
+use crate::dhcpd_parser::parser;
+
+const JOURNAL: &str = "
+    lease 192.168.0.2 {
+        starts 2 2019/01/01 22:00:00 UTC;
+        ends 2 2019/01/01 23:00:00 UTC;
+        hardware type 11:11:11:11:11:11;
+    }
+
+    lease 192.168.0.3 {
+        starts 1 2018/01/01 22:10:00 UTC;
+        ends 1 2018/01/01 23:10:00 UTC;
+        hardware type 22:22:22:22:22:22;
+    }
+
+    lease 192.168.0.2 {
+        starts 2 2019/01/01 23:00:00 UTC;
+        ends 3 2019/01/02 00:00:00 UTC;
+        hardware type 11:11:11:11:11:11;
+        uid Client1;
+        client-hostname \"CLIENTHOSTNAME\";
+        hostname \"TESTHOSTNAME\";
+        abandoned;
+    }
+";
+
+#[test]
+fn compact_test() {
+    let leases = parser::parse(JOURNAL).unwrap().leases;
+    let now = Date::from("4", "2019/01/10", "00:00:00").unwrap();
+
+    let compacted = compact::compact(&leases, &CompactOptions::default(), now);
+    assert_eq!(compacted.len(), 2);
+    assert_eq!(compacted[0], leases[1]);
+    assert_eq!(compacted[1], leases[2]);
+
+    let options = CompactOptions {
+        drop_expired_after_days: Some(30),
+    };
+    let compacted = compact::compact(&leases, &options, now);
+    assert_eq!(compacted.len(), 1);
+    assert_eq!(compacted[0], leases[2]);
+}
+
+#[test]
+fn write_leases_roundtrip_test() {
+    let leases = parser::parse(JOURNAL).unwrap().leases;
+
+    let mut out = Vec::new();
+    compact::write_leases(&mut out, &leases).unwrap();
+    let written = String::from_utf8(out).unwrap();
+
+    assert!(written.starts_with(
+        "lease 192.168.0.2 {\n\tstarts 2 2019/01/01 22:00:00;\n\tends 2 2019/01/01 23:00:00;\n"
+    ));
+    assert_eq!(parser::parse(written).unwrap().leases, leases);
+}
+
+#[test]
+fn compact_file_test() {
+    let dir = env::temp_dir().join(format!("dhcpd-parser-compact-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("dhcpd.leases");
+    fs::write(&path, JOURNAL).unwrap();
+
+    let compacted = compact::compact_file(&path, &CompactOptions::default()).unwrap();
+    assert_eq!(compacted.len(), 2);
+
+    let backup = fs::read_to_string(dir.join("dhcpd.leases~")).unwrap();
+    assert_eq!(backup, JOURNAL);
+    let rewritten = fs::read_to_string(&path).unwrap();
+    assert_eq!(parser::parse(rewritten).unwrap().leases, compacted);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,187 @@
+extern crate dhcpd_parser;
+
+use crate::dhcpd_parser::common::Date;
+use crate::dhcpd_parser::conflicts;
+use crate::dhcpd_parser::leases::ClientId;
+use crate::dhcpd_parser::parser;
+use crate::dhcpd_parser::validate::{self, Code};
+
+fn date(t: &str) -> Option<Date> {
+    Some(Date::from("2", "2019/01/01", t).unwrap())
+}
+
+#[test]
+fn no_conflicts_test() {
+    // renewal, early release followed by another client and an abandoned
+    // declaration of the same address
+    let leases = parser::parse(
+        "
+        lease 10.0.0.2 {
+            starts 2 2019/01/01 10:00:00;
+            ends 2 2019/01/01 14:00:00;
+            hardware ethernet 00:00:00:00:00:01;
+        }
+        lease 10.0.0.2 {
+            starts 2 2019/01/01 12:00:00;
+            ends 2 2019/01/01 16:00:00;
+            hardware ethernet 00:00:00:00:00:01;
+        }
+        lease 10.0.0.2 {
+            starts 2 2019/01/01 12:00:00;
+            ends 2 2019/01/01 13:00:00;
+            hardware ethernet 00:00:00:00:00:01;
+        }
+        lease 10.0.0.2 {
+            starts 2 2019/01/01 13:00:01;
+            ends 2 2019/01/01 18:00:00;
+            hardware ethernet 00:00:00:00:00:02;
+        }
+        lease 10.0.0.2 {
+            starts 2 2019/01/01 14:00:00;
+            abandoned;
+        }
+        ",
+    )
+    .unwrap()
+    .leases;
+
+    assert!(conflicts::detect(&leases).is_empty());
+}
+
+#[test]
+fn ip_conflict_test() {
+    let leases = parser::parse(
+        "
+        lease 10.0.0.2 {
+            starts 2 2019/01/01 10:00:00;
+            ends 2 2019/01/01 12:00:00;
+            hardware ethernet 00:00:00:00:00:01;
+        }
+        lease 10.0.0.2 {
+            starts 2 2019/01/01 11:00:00;
+            ends 2 2019/01/01 14:00:00;
+            uid 01:02;
+        }
+        lease 10.0.0.2 {
+            starts 2 2019/01/01 11:30:00;
+            ends 2 2019/01/01 13:00:00;
+            hardware ethernet 00:00:00:00:00:01;
+        }
+        lease 10.0.0.3 {
+            starts 2 2019/01/01 14:00:00;
+            ends 2 2019/01/01 16:00:00;
+            hardware ethernet 00:00:00:00:00:03;
+        }
+        lease 10.0.0.3 {
+            starts 2 2019/01/01 16:00:00;
+            hardware ethernet 00:00:00:00:00:04;
+            uid 03:03;
+        }
+        lease 10.0.0.3 {
+            starts 2 2019/01/01 17:00:00;
+            uid 03:03;
+        }
+        ",
+    )
+    .unwrap()
+    .leases;
+
+    let res = conflicts::detect(&leases);
+    assert_eq!(res.ips.len(), 1);
+
+    // the renewal continues the conflict of the first declaration
+    let c = &res.ips[0];
+    assert_eq!(c.ip, "10.0.0.2");
+    assert_eq!(
+        c.clients,
+        [
+            ClientId::MAC("00:00:00:00:00:01".to_owned()),
+            ClientId::Uid("01:02".to_owned())
+        ]
+    );
+    assert_eq!((c.from, c.to), (date("11:00:00"), date("13:00:00")));
+    assert_eq!(c.leases, [0, 1, 2]);
+
+    // 10.0.0.3 is handed over the second the first lease ends, and the same
+    // client is seen by its uid without its hardware address later
+
+    assert!(res.macs.is_empty());
+}
+
+#[test]
+fn mac_conflict_test() {
+    let leases = parser::parse(
+        "
+        lease 10.0.0.2 {
+            starts 2 2019/01/01 10:00:00;
+            ends 2 2019/01/01 12:00:00;
+            hardware ethernet 00:00:00:00:00:01;
+        }
+        lease 10.0.0.3 {
+            starts 2 2019/01/01 11:00:00;
+            hardware ethernet 00:00:00:00:00:01;
+        }
+        lease 10.0.0.4 {
+            starts 2 2019/01/01 12:00:01;
+            ends 2 2019/01/01 13:00:00;
+            hardware ethernet 00:00:00:00:00:01;
+        }
+        ",
+    )
+    .unwrap()
+    .leases;
+
+    let res = conflicts::detect(&leases);
+    assert!(res.ips.is_empty());
+    assert_eq!(res.macs.len(), 2);
+
+    let c = &res.macs[0];
+    assert_eq!(c.mac, "00:00:00:00:00:01");
+    assert_eq!(c.ips, ["10.0.0.2".to_owned(), "10.0.0.3".to_owned()]);
+    assert_eq!((c.from, c.to), (date("11:00:00"), date("12:00:00")));
+    assert_eq!(c.leases, [0, 1]);
+
+    let c = &res.macs[1];
+    assert_eq!(c.ips, ["10.0.0.3".to_owned(), "10.0.0.4".to_owned()]);
+    assert_eq!((c.from, c.to), (date("12:00:01"), date("13:00:00")));
+    assert_eq!(c.leases, [1, 2]);
+}
+
+#[test]
+fn client_key_test() {
+    let input = "
+        lease 10.0.0.2 {
+            starts 2 2019/01/01 10:00:00;
+            ends 2 2019/01/01 14:00:00;
+            hardware ethernet 00:1A:2B:3C:4D:5E;
+            uid 01:00:1A:2B:3C:4D:5E;
+        }
+        lease 10.0.0.2 {
+            starts 2 2019/01/01 11:00:00;
+            ends 2 2019/01/01 15:00:00;
+            uid 01:00:1a:2b:3c:4d:5e;
+        }
+        lease 10.0.0.3 {
+            starts 2 2019/01/01 10:00:00;
+            ends 2 2019/01/01 12:00:00;
+            hardware ethernet 00:1A:2B:3C:4D:60;
+        }
+        lease 10.0.0.3 {
+            starts 2 2019/01/01 11:00:00;
+            ends 2 2019/01/01 13:00:00;
+            hardware ethernet 00:1a:2b:3c:4d:60;
+        }
+    ";
+    let res = parser::parse(input).unwrap();
+
+    // conflicts, the L006 check and the client key agree on who holds a lease
+    assert_eq!(
+        res.leases[0].client_id(),
+        Some(ClientId::Uid("01:00:1a:2b:3c:4d:5e".to_owned()))
+    );
+    assert_eq!(res.leases[2].client_id(), res.leases[3].client_id());
+    assert!(conflicts::detect(&res.leases).ips.is_empty());
+    assert!(validate::validate(&res)
+        .iter()
+        .all(|f| f.code != Code::OverlappingLease));
+}
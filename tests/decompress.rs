@@ -0,0 +1,105 @@
+extern crate dhcpd_parser;
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+
+use crate::dhcpd_parser::decompress::{self, Compression};
+use crate::dhcpd_parser::parser;
+use crate::dhcpd_parser::reader::LeaseReader;
+
+const LEASES: &str = "
+    lease 10.0.0.2 {
+        starts 2 2019/01/01 10:00:00;
+        hardware ethernet 11:11:11:11:11:11;
+    }
+
+    lease 10.0.0.3 {
+        starts 2 2019/01/01 11:00:00;
+        hardware ethernet 22:22:22:22:22:22;
+    }
+";
+
+fn write_temp(name: &str, content: &[u8]) -> PathBuf {
+    let dir = env::temp_dir().join(format!("dhcpd-parser-decompress-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    fs::write(&path, content).unwrap();
+
+    path
+}
+
+fn check_parses(path: &PathBuf) {
+    let expected = parser::parse(LEASES).unwrap();
+    assert_eq!(parser::parse_path(path).unwrap(), expected);
+
+    let leases: Vec<_> = LeaseReader::open(path)
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(leases.len(), 2);
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn detect_test() {
+    assert_eq!(Compression::detect(b"lease 10.0.0.2"), Compression::None);
+    assert_eq!(Compression::detect(&[0x1f, 0x8b, 0x08]), Compression::Gzip);
+    assert_eq!(
+        Compression::detect(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]),
+        Compression::Xz
+    );
+    assert_eq!(
+        Compression::detect(&[0x28, 0xb5, 0x2f, 0xfd, 0x00]),
+        Compression::Zstd
+    );
+    assert_eq!(Compression::detect(&[]), Compression::None);
+}
+
+#[test]
+fn uncompressed_test() {
+    check_parses(&write_temp("dhcpd.leases", LEASES.as_bytes()));
+
+    let mut out = String::new();
+    std::io::Read::read_to_string(&mut decompress::decompress(&b"le"[..]).unwrap(), &mut out)
+        .unwrap();
+    assert_eq!(out, "le");
+}
+
+#[cfg(not(feature = "gzip"))]
+#[test]
+fn missing_feature_test() {
+    let path = write_temp("dhcpd.leases.1.gz", &[0x1f, 0x8b, 0x08, 0x00]);
+    let err = parser::parse_path(&path).unwrap_err();
+    assert!(err.ends_with("Input is gzip compressed, which requires the `gzip` feature"));
+    fs::remove_file(path).unwrap();
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn gzip_test() {
+    use std::io::Write;
+
+    let mut enc = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    enc.write_all(LEASES.as_bytes()).unwrap();
+    check_parses(&write_temp("dhcpd.leases.1.gz", &enc.finish().unwrap()));
+}
+
+#[cfg(feature = "xz")]
+#[test]
+fn xz_test() {
+    use std::io::Write;
+
+    let mut enc = xz2::write::XzEncoder::new(Vec::new(), 6);
+    enc.write_all(LEASES.as_bytes()).unwrap();
+    check_parses(&write_temp("dhcpd.leases.2.xz", &enc.finish().unwrap()));
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn zstd_test() {
+    let compressed = zstd::encode_all(LEASES.as_bytes(), 3).unwrap();
+    check_parses(&write_temp("dhcpd.leases.3.zst", &compressed));
+}
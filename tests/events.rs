@@ -0,0 +1,175 @@
+extern crate dhcpd_parser;
+
+use crate::dhcpd_parser::common::Date;
+use crate::dhcpd_parser::events::{diff, diff_at, LeaseEventKind};
+use crate::dhcpd_parser::parser;
+
+const OLD: &str = "
+    lease 10.0.0.2 {
+        starts 2 2019/01/01 10:00:00;
+        ends 2 2019/01/01 12:00:00;
+        hardware ethernet 11:11:11:11:11:11;
+    }
+
+    lease 10.0.0.3 {
+        starts 2 2019/01/01 10:00:00;
+        ends 2 2019/01/01 12:00:00;
+        hardware ethernet 22:22:22:22:22:22;
+    }
+
+    lease 10.0.0.4 {
+        starts 2 2019/01/01 10:00:00;
+        ends 2 2019/01/01 12:00:00;
+        hardware ethernet 33:33:33:33:33:33;
+    }
+
+    lease 10.0.0.5 {
+        starts 2 2019/01/01 10:00:00;
+        ends 2 2019/01/01 12:00:00;
+        hardware ethernet 44:44:44:44:44:44;
+    }
+
+    lease 10.0.0.6 {
+        starts 2 2019/01/01 10:00:00;
+        ends 2 2019/01/01 12:00:00;
+        hardware ethernet 55:55:55:55:55:55;
+    }
+
+    lease 10.0.0.7 {
+        starts 2 2019/01/01 10:00:00;
+        ends 2 2019/01/01 12:00:00;
+        hardware ethernet 66:66:66:66:66:66;
+    }
+";
+
+const NEW: &str = "
+    lease 10.0.0.2 {
+        starts 2 2019/01/01 10:00:00;
+        ends 2 2019/01/01 12:00:00;
+        hardware ethernet 11:11:11:11:11:11;
+    }
+
+    lease 10.0.0.3 {
+        starts 2 2019/01/01 11:00:00;
+        ends 2 2019/01/01 13:00:00;
+        hardware ethernet 22:22:22:22:22:22;
+    }
+
+    lease 10.0.0.4 {
+        starts 2 2019/01/01 10:00:00;
+        ends 2 2019/01/01 11:00:00;
+        hardware ethernet 33:33:33:33:33:33;
+    }
+
+    lease 10.0.0.5 {
+        starts 2 2019/01/01 11:00:00;
+        ends 2 2019/01/01 13:00:00;
+        hardware ethernet 77:77:77:77:77:77;
+    }
+
+    lease 10.0.0.6 {
+        starts 2 2019/01/01 11:00:00;
+        ends 2 2019/01/01 13:00:00;
+        abandoned;
+    }
+
+    lease 10.0.0.8 {
+        starts 2 2019/01/01 11:00:00;
+        ends 2 2019/01/01 13:00:00;
+        hardware ethernet 88:88:88:88:88:88;
+    }
+";
+
+#[test]
+fn diff_test() {
+    let old = parser::parse(OLD).unwrap().leases;
+    let new = parser::parse(NEW).unwrap().leases;
+
+    let events: Vec<(LeaseEventKind, String)> = diff(&old, &new)
+        .into_iter()
+        .map(|e| (e.kind, e.ip))
+        .collect();
+
+    assert_eq!(
+        events,
+        vec![
+            (LeaseEventKind::Renewed, "10.0.0.3".to_owned()),
+            (LeaseEventKind::Released, "10.0.0.4".to_owned()),
+            (LeaseEventKind::Reassigned, "10.0.0.5".to_owned()),
+            (LeaseEventKind::Abandoned, "10.0.0.6".to_owned()),
+            (LeaseEventKind::Granted, "10.0.0.8".to_owned()),
+            (LeaseEventKind::Expired, "10.0.0.7".to_owned()),
+        ]
+    );
+}
+
+#[test]
+fn diff_journal_test() {
+    let old = parser::parse(OLD).unwrap().leases;
+    // renewal appended to the journal
+    let new = parser::parse(format!(
+        "{}
+    lease 10.0.0.2 {{
+        starts 2 2019/01/01 11:00:00;
+        ends 2 2019/01/01 13:00:00;
+        hardware ethernet 11:11:11:11:11:11;
+    }}",
+        OLD
+    ))
+    .unwrap()
+    .leases;
+
+    let events = diff(&old, &new);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].kind, LeaseEventKind::Renewed);
+    assert_eq!(events[0].old.as_ref().unwrap(), &old[0]);
+    assert_eq!(events[0].new.as_ref().unwrap(), &new[6]);
+
+    assert!(diff(&new, &new).is_empty());
+}
+
+#[test]
+fn diff_at_test() {
+    let old = parser::parse(OLD).unwrap().leases;
+    let new = parser::parse(NEW).unwrap().leases;
+    let at = |t| Date::from("2", "2019/01/01", t).unwrap();
+
+    let events: Vec<(LeaseEventKind, String)> = diff_at(&old, &new, at("11:30:00"), at("12:00:00"))
+        .into_iter()
+        .map(|e| (e.kind, e.ip))
+        .collect();
+
+    // 10.0.0.2 is unchanged and ran out
+    assert_eq!(
+        events,
+        vec![
+            (LeaseEventKind::Expired, "10.0.0.2".to_owned()),
+            (LeaseEventKind::Renewed, "10.0.0.3".to_owned()),
+            (LeaseEventKind::Released, "10.0.0.4".to_owned()),
+            (LeaseEventKind::Reassigned, "10.0.0.5".to_owned()),
+            (LeaseEventKind::Abandoned, "10.0.0.6".to_owned()),
+            (LeaseEventKind::Granted, "10.0.0.8".to_owned()),
+            (LeaseEventKind::Expired, "10.0.0.7".to_owned()),
+        ]
+    );
+
+    // reported once, not again once it has already run out
+    assert_eq!(diff_at(&new, &new, at("11:30:00"), at("11:59:59")), vec![]);
+    assert_eq!(diff_at(&new, &new, at("12:00:00"), at("13:00:00")).len(), 3);
+    assert!(diff_at(&new, &new, at("13:00:00"), at("14:00:00")).is_empty());
+}
+
+#[test]
+fn diff_client_case_test() {
+    let old = parser::parse(OLD).unwrap().leases;
+    let new = parser::parse(OLD.replace("66:66:66:66:66:66", "AA:BB:CC:DD:EE:FF"))
+        .unwrap()
+        .leases;
+    let upper = parser::parse(OLD.replace("66:66:66:66:66:66", "aa:bb:cc:dd:ee:ff"))
+        .unwrap()
+        .leases;
+
+    assert_eq!(diff(&old, &new)[0].kind, LeaseEventKind::Reassigned);
+    // only the case of the hardware address changes
+    assert!(diff(&new, &upper).is_empty());
+}
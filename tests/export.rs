@@ -0,0 +1,153 @@
+extern crate dhcpd_parser;
+
+use crate::dhcpd_parser::common::Date;
+use crate::dhcpd_parser::export::{self, Column, ExportOptions};
+use crate::dhcpd_parser::leases::{Lease, Leases, LeasesMethods};
+use crate::dhcpd_parser::parser;
+
+const JOURNAL: &str = "
+    lease 10.0.0.2 {
+        starts 2 2019/01/01 10:00:00;
+        ends 2 2019/01/01 12:00:00;
+        hardware ethernet 11:11:11:11:11:11;
+        client-hostname \"a,b\";
+    }
+
+    lease 10.0.0.3 {
+        starts 2 2019/01/01 10:00:00;
+        abandoned;
+    }
+
+    lease 10.0.0.2 {
+        starts 2 2019/01/01 11:00:00;
+        ends 2 2019/01/01 13:00:00;
+        hardware ethernet 11:11:11:11:11:11;
+    }
+";
+
+fn options(columns: &[Column], current_only: bool) -> ExportOptions {
+    ExportOptions {
+        columns: columns.to_vec(),
+        current_only,
+        now: Date::from("2", "2019/01/01", "12:30:00").unwrap(),
+    }
+}
+
+fn export<F>(f: F, options: &ExportOptions) -> String
+where
+    F: Fn(&mut Vec<u8>, &Leases, &ExportOptions) -> Result<(), String>,
+{
+    let leases = parser::parse(JOURNAL).unwrap().leases;
+    let mut out = Vec::new();
+    f(&mut out, &leases, options).unwrap();
+
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn csv_test() {
+    let opts = options(&[Column::Ip, Column::ClientHostname, Column::State], false);
+    assert_eq!(
+        export(export::write_csv, &opts),
+        "ip,client-hostname,state\r\n\
+         10.0.0.2,\"a,b\",expired\r\n\
+         10.0.0.3,,abandoned\r\n\
+         10.0.0.2,,active\r\n"
+    );
+
+    let opts = options(&[Column::Ip, Column::Ends], true);
+    assert_eq!(
+        export(export::write_csv, &opts),
+        "ip,ends\r\n10.0.0.3,\r\n10.0.0.2,2019-01-01T13:00:00Z\r\n"
+    );
+}
+
+#[test]
+fn json_test() {
+    let opts = options(&[Column::Ip, Column::Mac, Column::ClientHostname], false);
+    assert_eq!(
+        export(export::write_json, &opts),
+        "[\n  \
+         {\"ip\":\"10.0.0.2\",\"hardware\":{\"h_type\":\"ethernet\",\"mac\":\"11:11:11:11:11:11\"},\
+         \"client_hostname\":\"a,b\"},\n  \
+         {\"ip\":\"10.0.0.3\",\"hardware\":null,\"client_hostname\":null},\n  \
+         {\"ip\":\"10.0.0.2\",\"hardware\":{\"h_type\":\"ethernet\",\"mac\":\"11:11:11:11:11:11\"},\
+         \"client_hostname\":null}\n\
+         ]\n"
+    );
+
+    let opts = options(&[Column::Ip], false);
+    let leases = parser::parse("").unwrap().leases;
+    let mut out = Vec::new();
+    export::write_json(&mut out, &leases, &opts).unwrap();
+    assert_eq!(out, b"[]\n");
+}
+
+#[test]
+fn ndjson_test() {
+    let opts = options(&[Column::Ip, Column::Starts, Column::State], true);
+    assert_eq!(
+        export(export::write_ndjson, &opts),
+        "{\"ip\":\"10.0.0.3\",\"dates\":{\"starts\":\"2019-01-01T10:00:00Z\"},\"state\":\"abandoned\"}\n\
+         {\"ip\":\"10.0.0.2\",\"dates\":{\"starts\":\"2019-01-01T11:00:00Z\"},\"state\":\"active\"}\n"
+    );
+}
+
+#[test]
+fn column_test() {
+    assert_eq!(
+        Column::from("client-hostname").unwrap(),
+        Column::ClientHostname
+    );
+    assert!(Column::from("vendor").is_err());
+    for c in Column::ALL.iter() {
+        assert_eq!(&Column::from(c.name()).unwrap(), c);
+    }
+}
+
+#[test]
+fn escaping_test() {
+    let mut leases = Leases::new();
+    leases.push(Lease {
+        ip: "10.0.0.4".to_owned(),
+        client_hostname: Some("say \"hi\"\\\n".to_owned()),
+        ..Lease::default()
+    });
+    let opts = options(&[Column::ClientHostname], false);
+
+    let mut out = Vec::new();
+    export::write_ndjson(&mut out, &leases, &opts).unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "{\"client_hostname\":\"say \\\"hi\\\"\\\\\\n\"}\n"
+    );
+
+    let mut out = Vec::new();
+    export::write_csv(&mut out, &leases, &opts).unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "client-hostname\r\n\"say \"\"hi\"\"\\\n\"\r\n"
+    );
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn json_matches_serde_test() {
+    let leases = parser::parse(JOURNAL).unwrap().leases;
+    let columns: Vec<Column> = Column::ALL
+        .iter()
+        .copied()
+        .filter(|&c| c != Column::State)
+        .collect();
+
+    let mut out = Vec::new();
+    export::write_ndjson(&mut out, &leases, &options(&columns, false)).unwrap();
+    let out = String::from_utf8(out).unwrap();
+
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines.len(), leases.len());
+    for (line, l) in lines.iter().zip(leases.iter()) {
+        assert_eq!(*line, serde_json::to_string(l).unwrap());
+        assert_eq!(&serde_json::from_str::<Lease>(line).unwrap(), l);
+    }
+}
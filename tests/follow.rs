@@ -0,0 +1,100 @@
+extern crate dhcpd_parser;
+
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{Read, Write};
+use std::process;
+
+use crate::dhcpd_parser::follow::LeaseFollower;
+use crate::dhcpd_parser::reader::LeaseReader;
+
+const LEASE_1: &str = "lease 10.0.0.2 {
+\tstarts 2 2019/01/01 10:00:00;
+\thardware ethernet 11:11:11:11:11:11;
+\tclient-hostname \"K{ü}\";
+}
+";
+
+const LEASE_2: &str = "lease 10.0.0.3 {
+\tstarts 2 2019/01/01 11:00:00;
+\thardware ethernet 22:22:22:22:22:22;
+}
+";
+
+/// Reads one byte at a time
+struct Trickle<'a>(&'a [u8]);
+
+impl<'a> Read for Trickle<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.0.is_empty() || buf.is_empty() {
+            return Ok(0);
+        }
+        buf[0] = self.0[0];
+        self.0 = &self.0[1..];
+        Ok(1)
+    }
+}
+
+#[test]
+fn lease_reader_test() {
+    let input = format!("{}\n{}", LEASE_1, LEASE_2);
+    let leases: Vec<_> = LeaseReader::new(Trickle(input.as_bytes()))
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    assert_eq!(leases.len(), 2);
+    assert_eq!(leases[0].client_hostname.as_ref().unwrap(), "K{ü}");
+    assert_eq!(leases[1].ip, "10.0.0.3");
+
+    let truncated = &LEASE_2[..LEASE_2.len() - 3];
+    let res: Vec<_> = LeaseReader::new(format!("{}{}", LEASE_1, truncated).as_bytes()).collect();
+    assert_eq!(res.len(), 2);
+    assert!(res[0].is_ok());
+    assert!(res[1].is_err());
+}
+
+#[test]
+fn follow_test() {
+    let dir = env::temp_dir().join(format!("dhcpd-parser-follow-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("dhcpd.leases");
+
+    let half = LEASE_2.len() / 2;
+    fs::write(&path, format!("{}{}", LEASE_1, &LEASE_2[..half])).unwrap();
+
+    let mut follower = LeaseFollower::open(&path).unwrap();
+    assert_eq!(follower.leases().len(), 1);
+
+    // half-written lease is not reported
+    let batch = follower.poll().unwrap();
+    assert!(batch.leases.is_empty());
+    assert!(!batch.resynced);
+
+    let mut f = OpenOptions::new().append(true).open(&path).unwrap();
+    f.write_all(&LEASE_2.as_bytes()[half..]).unwrap();
+    drop(f);
+
+    let batch = follower.poll().unwrap();
+    assert_eq!(batch.leases.len(), 1);
+    assert_eq!(batch.leases[0].ip, "10.0.0.3");
+    assert_eq!(follower.leases().len(), 2);
+
+    // dhcpd rewrites the file and renames it over the old one
+    let tmp = dir.join("dhcpd.leases.new");
+    fs::write(&tmp, LEASE_2).unwrap();
+    fs::rename(&tmp, &path).unwrap();
+
+    let batch = follower.poll().unwrap();
+    assert!(batch.resynced);
+    assert_eq!(batch.leases.len(), 1);
+    assert_eq!(follower.leases().len(), 1);
+    assert_eq!(follower.leases()[0].ip, "10.0.0.3");
+
+    // truncated in place
+    fs::write(&path, "").unwrap();
+    let batch = follower.poll().unwrap();
+    assert!(batch.resynced);
+    assert!(follower.leases().is_empty());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
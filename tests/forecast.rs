@@ -0,0 +1,91 @@
+extern crate dhcpd_parser;
+
+use std::time::Duration;
+
+use crate::dhcpd_parser::common::Date;
+use crate::dhcpd_parser::forecast::{self, ForecastOptions};
+use crate::dhcpd_parser::net::AddressRange;
+use crate::dhcpd_parser::parser;
+
+fn date(d: &str, t: &str) -> Date {
+    Date::from_timestamp(Date::from("0", d, t).unwrap().timestamp())
+}
+
+/// Two more clients every day between 12:00 and 14:00
+fn journal() -> String {
+    let mut res = String::new();
+    for day in 1..=3 {
+        for host in 1..=2 * day {
+            res.push_str(&format!(
+                "lease 10.0.0.{host} {{
+                    starts 0 2019/01/0{day} 12:00:00;
+                    ends 0 2019/01/0{day} 14:00:00;
+                    hardware ethernet 00:00:00:00:0{day}:0{host};
+                }}\n",
+                host = host,
+                day = day,
+            ));
+        }
+    }
+    res.push_str(
+        "lease 10.0.1.1 {
+            starts 0 2019/01/01 00:00:00;
+            hardware ethernet 00:00:00:00:00:ff;
+        }",
+    );
+
+    res
+}
+
+fn options() -> ForecastOptions {
+    ForecastOptions {
+        window: Duration::from_secs(3 * 86400),
+        now: date("2019/01/04", "00:00:00"),
+        ..ForecastOptions::default()
+    }
+}
+
+#[test]
+fn forecast_test() {
+    let leases = parser::parse(journal()).unwrap().leases;
+    let pool = AddressRange::from("10.0.0.1-10.0.0.10").unwrap();
+    let f = forecast::forecast(&leases, pool, &options()).unwrap();
+
+    assert_eq!(f.size, 10);
+    assert_eq!(f.active, 0);
+    assert_eq!(f.peak, 6);
+    assert_eq!(f.peak_hour, Some(12));
+    assert_eq!(f.hourly_peaks[13], 6);
+    assert_eq!(f.hourly_peaks[14], 0);
+    assert_eq!(f.headroom, 4);
+    assert_eq!(f.peak_utilisation(), 0.6);
+    assert_eq!(f.growth, Some(2.0));
+    assert_eq!(f.exhaustion, Some(date("2019/01/05", "00:00:00")));
+}
+
+#[test]
+fn no_growth_test() {
+    let leases = parser::parse(journal()).unwrap().leases;
+
+    let pool = AddressRange::from("10.0.1.0/24").unwrap();
+    let f = forecast::forecast(&leases, pool, &options()).unwrap();
+    assert_eq!(f.active, 1);
+    assert_eq!(f.peak, 1);
+    assert_eq!(f.growth, Some(0.0));
+    assert_eq!(f.exhaustion, None);
+
+    // already full at the peak
+    let pool = AddressRange::from("10.0.0.1-10.0.0.4").unwrap();
+    let f = forecast::forecast(&leases, pool, &options()).unwrap();
+    assert_eq!(f.headroom, 0);
+    assert_eq!(f.exhaustion, Some(options().now));
+
+    let opts = ForecastOptions {
+        window: Duration::from_secs(3600),
+        ..options()
+    };
+    assert_eq!(
+        forecast::forecast(&leases, pool, &opts).unwrap().growth,
+        None
+    );
+}
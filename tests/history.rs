@@ -0,0 +1,89 @@
+extern crate dhcpd_parser;
+
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process;
+use std::time::{Duration, SystemTime};
+
+use crate::dhcpd_parser::history;
+
+const ROTATED: &str = "lease 10.0.0.2 {
+\tstarts 2 2019/01/01 10:00:00;
+\thardware ethernet 11:11:11:11:11:11;
+}
+lease 10.0.0.3 {
+\tstarts 2 2019/01/01 12:00:00;
+\thardware ethernet 22:22:22:22:22:22;
+}
+";
+
+const LIVE: &str = "lease 10.0.0.3 {
+\tstarts 2 2019/01/01 12:00:00;
+\thardware ethernet 22:22:22:22:22:22;
+}
+
+lease 10.0.0.2 {
+\tstarts 2 2019/01/01 11:00:00;
+\thardware ethernet 11:11:11:11:11:11;
+}
+";
+
+fn write(path: &Path, content: &str, age: u64) {
+    fs::write(path, content).unwrap();
+    let f = fs::File::options().write(true).open(path).unwrap();
+    f.set_modified(SystemTime::now() - Duration::from_secs(age))
+        .unwrap();
+}
+
+#[test]
+fn load_test() {
+    let dir = env::temp_dir().join(format!("dhcpd-parser-history-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    write(&dir.join("dhcpd.leases"), LIVE, 0);
+    write(&dir.join("dhcpd.leases.1"), ROTATED, 3600);
+    write(
+        &dir.join("dhcpd.conf"),
+        "option domain-name \"example\";",
+        0,
+    );
+
+    let history = history::load(dir.join("dhcpd.leases*")).unwrap();
+    assert_eq!(history.len(), 3);
+
+    let ips: Vec<&str> = history.leases().iter().map(|l| l.ip.as_str()).collect();
+    assert_eq!(ips, vec!["10.0.0.2", "10.0.0.2", "10.0.0.3"]);
+
+    // identical declaration from both files
+    let prov = history.provenance(2);
+    assert_eq!(prov.len(), 2);
+    assert_eq!(prov[0].path, dir.join("dhcpd.leases.1"));
+    assert_eq!(prov[0].line, 5);
+    assert_eq!(prov[1].path, dir.join("dhcpd.leases"));
+    assert_eq!(prov[1].line, 1);
+
+    assert_eq!(history.provenance(1)[0].line, 6);
+
+    // the configuration file is not a leases file
+    assert!(history::load(&dir).is_err());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn load_files_test() {
+    let dir = env::temp_dir().join(format!("dhcpd-parser-history-files-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    write(&dir.join("a"), LIVE, 0);
+    write(&dir.join("b"), LIVE, 0);
+
+    let history = history::load_files(vec![dir.join("a"), dir.join("b")]).unwrap();
+    assert_eq!(history.len(), 2);
+    for (_, prov) in history.iter() {
+        assert_eq!(prov.len(), 2);
+    }
+
+    assert!(history::load_files(vec![dir.join("c")]).is_err());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,191 @@
+extern crate dhcpd_parser;
+
+use crate::dhcpd_parser::common::Date;
+use crate::dhcpd_parser::hostnames::{self, NameSource};
+use crate::dhcpd_parser::leases::ClientId;
+use crate::dhcpd_parser::parser;
+
+#[test]
+fn hostname_report_test() {
+    let leases = parser::parse(
+        "
+        lease 10.0.0.2 {
+            starts 2 2019/01/01 10:00:00;
+            ends 2 2019/01/01 12:00:00;
+            hardware ethernet 00:00:00:00:00:01;
+            client-hostname \"laptop\";
+        }
+        lease 10.0.0.2 {
+            starts 2 2019/01/01 12:00:00;
+            ends 2 2019/01/01 14:00:00;
+            hardware ethernet 00:00:00:00:00:01;
+            client-hostname \"laptop\";
+            hostname \"printer\";
+        }
+        lease 10.0.0.3 {
+            starts 2 2019/01/01 09:00:00;
+            ends 2 2019/01/01 11:00:00;
+            uid 01:02;
+            client-hostname \"Laptop\";
+        }
+        lease 10.0.0.4 {
+            starts 2 2019/01/01 09:00:00;
+            client-hostname \"laptop\";
+        }
+        ",
+    )
+    .unwrap()
+    .leases;
+
+    let report = hostnames::report(&leases);
+    assert_eq!(report.len(), 2);
+
+    let laptop = report.get("LAPTOP", NameSource::ClientHostname).unwrap();
+    assert!(laptop.is_collision());
+    assert_eq!(laptop.name, "laptop");
+    assert_eq!(laptop.claims.len(), 2);
+
+    let first = &laptop.claims[0];
+    assert_eq!(first.client, ClientId::Uid("01:02".to_owned()));
+    assert_eq!(first.leases, [2]);
+
+    let second = &laptop.claims[1];
+    assert_eq!(second.client, ClientId::MAC("00:00:00:00:00:01".to_owned()));
+    assert_eq!(second.from, Date::from("2", "2019/01/01", "10:00:00").ok());
+    assert_eq!(second.to, Date::from("2", "2019/01/01", "14:00:00").ok());
+    assert_eq!(second.leases, [0, 1]);
+
+    let printer = report.get("printer", NameSource::Hostname).unwrap();
+    assert!(!printer.is_collision());
+    assert!(report.get("printer", NameSource::ClientHostname).is_none());
+
+    let collisions: Vec<&str> = report.collisions().map(|o| o.name.as_str()).collect();
+    assert_eq!(collisions, ["laptop"]);
+}
+
+#[test]
+fn collision_period_test() {
+    let leases = parser::parse(
+        "
+        lease 10.0.0.2 {
+            starts 2 2019/01/01 09:00:00;
+            ends 2 2019/01/01 10:00:00;
+            hardware ethernet 00:00:00:00:00:05;
+            client-hostname \"desktop\";
+        }
+        lease 10.0.0.3 {
+            starts 2 2019/01/01 10:00:00;
+            ends 2 2019/01/01 12:00:00;
+            hardware ethernet 00:00:00:00:00:06;
+            client-hostname \"desktop\";
+        }
+        lease 10.0.0.4 {
+            starts 2 2019/01/01 09:00:00;
+            hardware ethernet 00:00:00:00:00:07;
+            client-hostname \"phone\";
+        }
+        lease 10.0.0.4 {
+            starts 2 2019/01/01 10:00:00;
+            ends 2 2019/01/01 11:00:00;
+            hardware ethernet 00:00:00:00:00:07;
+            client-hostname \"phone\";
+        }
+        lease 10.0.0.5 {
+            starts 2 2019/01/01 20:00:00;
+            ends 2 2019/01/01 21:00:00;
+            hardware ethernet 00:00:00:00:00:08;
+            client-hostname \"phone\";
+        }
+        ",
+    )
+    .unwrap()
+    .leases;
+
+    let report = hostnames::report(&leases);
+
+    // handed over to another client when the first lease ends
+    let desktop = report.get("desktop", NameSource::ClientHostname).unwrap();
+    assert_eq!(desktop.claims.len(), 2);
+    assert!(!desktop.is_collision());
+    assert!(desktop.colliding().is_empty());
+
+    // a declaration without ends keeps the name claimed
+    let phone = report.get("phone", NameSource::ClientHostname).unwrap();
+    assert_eq!(phone.claims[0].to, None);
+    assert!(phone.is_collision());
+    assert_eq!(phone.colliding().len(), 2);
+
+    let collisions: Vec<&str> = report.collisions().map(|o| o.name.as_str()).collect();
+    assert_eq!(collisions, ["phone"]);
+}
+
+#[test]
+fn handover_test() {
+    let leases = parser::parse(
+        "
+        lease 10.0.0.2 {
+            starts 2 2019/01/01 08:00:00;
+            ends 2 2019/01/01 09:00:00;
+            hardware ethernet 00:00:00:00:00:05;
+            client-hostname \"desktop\";
+        }
+        lease 10.0.0.2 {
+            starts 2 2019/01/01 09:00:00;
+            ends 2 2019/01/01 10:00:00;
+            hardware ethernet 00:00:00:00:00:05;
+            client-hostname \"desktop\";
+        }
+        lease 10.0.0.3 {
+            starts 2 2019/01/01 10:00:00;
+            ends 2 2019/01/01 12:00:00;
+            hardware ethernet 00:00:00:00:00:06;
+            client-hostname \"desktop\";
+        }
+        lease 10.0.0.2 {
+            starts 2 2019/01/01 12:00:00;
+            ends 2 2019/01/01 14:00:00;
+            hardware ethernet 00:00:00:00:00:05;
+            client-hostname \"desktop\";
+        }
+        lease 10.0.0.4 {
+            ends 2 2019/01/01 09:00:00;
+            hardware ethernet 00:00:00:00:00:07;
+            client-hostname \"phone\";
+        }
+        lease 10.0.0.4 {
+            starts 2 2019/01/01 10:00:00;
+            ends 2 2019/01/01 11:00:00;
+            hardware ethernet 00:00:00:00:00:07;
+            client-hostname \"phone\";
+        }
+        ",
+    )
+    .unwrap()
+    .leases;
+    let at = |t| Date::from("2", "2019/01/01", t).ok();
+
+    let report = hostnames::report(&leases);
+
+    // handed from the first client to the second and back
+    let desktop = report.get("desktop", NameSource::ClientHostname).unwrap();
+    assert_eq!(desktop.claims.len(), 2);
+    assert!(!desktop.is_collision());
+    let first = &desktop.claims[0];
+    assert_eq!((first.from, first.to), (at("08:00:00"), at("14:00:00")));
+    assert_eq!(
+        first.periods,
+        [
+            (at("08:00:00"), at("10:00:00")),
+            (at("12:00:00"), at("14:00:00"))
+        ]
+    );
+    assert_eq!(first.leases, [0, 1, 3]);
+
+    // a declaration without starts leaves the claim unbounded
+    let phone = report.get("phone", NameSource::ClientHostname).unwrap();
+    assert_eq!(
+        (phone.claims[0].from, phone.claims[0].to),
+        (None, at("11:00:00"))
+    );
+    assert_eq!(phone.claims[0].periods.len(), 2);
+}
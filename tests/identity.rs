@@ -0,0 +1,224 @@
+extern crate dhcpd_parser;
+
+use crate::dhcpd_parser::identity::{self, CorrelateOptions, Evidence};
+use crate::dhcpd_parser::parser;
+
+#[test]
+fn is_randomised_test() {
+    assert!(identity::is_randomised("da:a1:19:00:00:01"));
+    assert!(identity::is_randomised("02:00:00:00:00:01"));
+    assert!(!identity::is_randomised("00:1a:2b:00:00:01"));
+    // multicast
+    assert!(!identity::is_randomised("03:00:00:00:00:01"));
+    assert!(!identity::is_randomised("zz:00:00:00:00:01"));
+    assert!(!identity::is_randomised(""));
+}
+
+fn lease(ip: &str, mac: &str, from: &str, to: &str, extra: &str) -> String {
+    format!(
+        "lease {} {{
+            starts 0 2019/01/{};
+            ends 0 2019/01/{};
+            hardware ethernet {};
+            {}
+        }}\n",
+        ip, from, to, mac, extra
+    )
+}
+
+#[test]
+fn correlate_test() {
+    let journal = [
+        // a phone changing its address, first right away, then a day later
+        lease(
+            "10.0.0.2",
+            "DA:00:00:00:00:01",
+            "01 10:00:00",
+            "01 12:00:00",
+            "client-hostname \"Pixel\";",
+        ),
+        lease(
+            "10.0.0.3",
+            "de:00:00:00:00:02",
+            "01 12:30:00",
+            "01 14:00:00",
+            "client-hostname \"pixel\";",
+        ),
+        lease(
+            "10.0.0.4",
+            "d2:00:00:00:00:03",
+            "02 10:00:00",
+            "02 11:00:00",
+            "client-hostname \"pixel\";",
+        ),
+        // a laptop docked and on wireless
+        lease(
+            "10.0.0.5",
+            "00:1a:2b:00:00:01",
+            "01 10:00:00",
+            "01 18:00:00",
+            "uid \"laptop\";",
+        ),
+        lease(
+            "10.0.0.6",
+            "00:1a:2b:00:00:02",
+            "01 10:00:00",
+            "01 18:00:00",
+            "uid \"laptop\";",
+        ),
+        // two phones with the same name at the same time
+        lease(
+            "10.0.0.7",
+            "da:00:00:00:00:10",
+            "01 10:00:00",
+            "01 12:00:00",
+            "client-hostname \"iphone\";",
+        ),
+        lease(
+            "10.0.0.8",
+            "da:00:00:00:00:11",
+            "01 11:00:00",
+            "01 13:00:00",
+            "client-hostname \"iphone\";",
+        ),
+    ]
+    .concat();
+    let leases = parser::parse(journal).unwrap().leases;
+
+    let clusters = identity::correlate(&leases, &CorrelateOptions::default());
+    assert_eq!(clusters.len(), 4);
+
+    let laptop = &clusters[0];
+    assert_eq!(laptop.macs, vec!["00:1a:2b:00:00:01", "00:1a:2b:00:00:02"]);
+    assert_eq!(laptop.uids, vec!["\"laptop\""]);
+    assert_eq!(laptop.evidence, vec![Evidence::Uid]);
+    assert_eq!(laptop.confidence, 0.95);
+    assert_eq!(laptop.randomised().count(), 0);
+
+    let phone = &clusters[1];
+    assert_eq!(
+        phone.macs,
+        vec![
+            "d2:00:00:00:00:03",
+            "da:00:00:00:00:01",
+            "de:00:00:00:00:02"
+        ]
+    );
+    assert_eq!(phone.hostnames, vec!["pixel"]);
+    assert_eq!(
+        phone.evidence,
+        vec![Evidence::ClientHostname, Evidence::Handover]
+    );
+    assert_eq!(phone.confidence, 0.6);
+    assert_eq!(phone.leases, vec![0, 1, 2]);
+    assert_eq!(phone.randomised().count(), 3);
+    assert_eq!(
+        phone.first_seen.unwrap().to_rfc3339(),
+        "2019-01-01T10:00:00Z"
+    );
+    assert_eq!(
+        phone.last_seen.unwrap().to_rfc3339(),
+        "2019-01-02T11:00:00Z"
+    );
+
+    for c in clusters[2..].iter() {
+        assert_eq!(c.macs.len(), 1);
+        assert!(c.evidence.is_empty());
+        assert_eq!(c.confidence, 1.0);
+    }
+}
+
+#[test]
+fn interleaved_hostname_test() {
+    // another device named the same is seen across both addresses of a tablet
+    let journal = [
+        lease(
+            "10.0.0.2",
+            "da:00:00:00:00:01",
+            "01 08:00:00",
+            "01 09:00:00",
+            "client-hostname \"ipad\";",
+        ),
+        lease(
+            "10.0.0.3",
+            "da:00:00:00:00:02",
+            "01 08:30:00",
+            "01 20:00:00",
+            "client-hostname \"ipad\";",
+        ),
+        lease(
+            "10.0.0.4",
+            "da:00:00:00:00:03",
+            "01 09:30:00",
+            "01 11:00:00",
+            "client-hostname \"ipad\";",
+        ),
+    ]
+    .concat();
+    let leases = parser::parse(journal).unwrap().leases;
+
+    // the name is held by two addresses at once
+    let options = CorrelateOptions {
+        max_concurrent: 2,
+        ..CorrelateOptions::default()
+    };
+    let clusters = identity::correlate(&leases, &options);
+    assert_eq!(clusters.len(), 2);
+
+    let tablet = clusters.iter().find(|c| c.macs.len() == 2).unwrap();
+    assert_eq!(tablet.macs, vec!["da:00:00:00:00:01", "da:00:00:00:00:03"]);
+    assert_eq!(tablet.evidence, vec![Evidence::Handover]);
+    assert_eq!(tablet.leases, vec![0, 2]);
+}
+
+#[test]
+fn generic_hostname_test() {
+    // two phones with the default name, each changing its address once
+    let journal = [
+        lease(
+            "10.0.0.2",
+            "da:00:00:00:00:01",
+            "01 08:00:00",
+            "01 10:00:00",
+            "client-hostname \"android\";",
+        ),
+        lease(
+            "10.0.0.3",
+            "da:00:00:00:00:02",
+            "01 09:00:00",
+            "01 11:00:00",
+            "client-hostname \"android\";",
+        ),
+        lease(
+            "10.0.0.4",
+            "da:00:00:00:00:03",
+            "01 12:00:00",
+            "01 14:00:00",
+            "client-hostname \"android\";",
+        ),
+        lease(
+            "10.0.0.5",
+            "da:00:00:00:00:04",
+            "01 13:00:00",
+            "01 15:00:00",
+            "client-hostname \"android\";",
+        ),
+    ]
+    .concat();
+    let leases = parser::parse(journal).unwrap().leases;
+
+    let clusters = identity::correlate(&leases, &CorrelateOptions::default());
+    assert_eq!(clusters.len(), 4);
+    for c in clusters.iter() {
+        assert_eq!(c.macs.len(), 1);
+        assert!(c.evidence.is_empty());
+    }
+
+    // trusting the name chains addresses of both phones
+    let options = CorrelateOptions {
+        max_concurrent: 2,
+        ..CorrelateOptions::default()
+    };
+    let clusters = identity::correlate(&leases, &options);
+    assert!(clusters.len() < 4);
+}
@@ -0,0 +1,114 @@
+extern crate dhcpd_parser;
+
+use crate::dhcpd_parser::common::Date;
+use crate::dhcpd_parser::index::LeaseIndex;
+use crate::dhcpd_parser::parser;
+
+const JOURNAL: &str = "
+    lease 10.1.2.3 {
+        starts 2 2019/01/01 10:00:00 UTC;
+        ends 2 2019/01/01 12:00:00 UTC;
+        hardware ethernet 11:11:11:11:11:11;
+    }
+
+    lease 10.1.2.4 {
+        starts 2 2019/01/01 11:00:00 UTC;
+        ends 2 2019/01/01 15:00:00 UTC;
+        hardware ethernet 22:22:22:22:22:22;
+    }
+
+    lease 10.1.2.3 {
+        starts 2 2019/01/01 12:30:00 UTC;
+        ends 2 2019/01/01 14:00:00 UTC;
+        hardware ethernet 33:33:33:33:33:33;
+    }
+
+    lease 10.1.2.5 {
+        starts 2 2019/01/01 16:00:00 UTC;
+        ends 2 2019/01/01 18:00:00 UTC;
+        hardware ethernet 11:11:11:11:11:11;
+    }
+
+    lease 10.1.2.6 {
+        starts 2 2019/01/01 20:00:00 UTC;
+        hardware ethernet 44:44:44:44:44:44;
+    }
+";
+
+fn at(time: &str) -> Date {
+    Date::from("2", "2019/01/01", time).unwrap()
+}
+
+#[test]
+fn ip_holders_at_test() {
+    let leases = parser::parse(JOURNAL).unwrap().leases;
+    let index = LeaseIndex::new(&leases);
+
+    let holders = index.ip_holders_at("10.1.2.3", at("11:30:00"));
+    assert_eq!(holders.len(), 1);
+    assert_eq!(holders[0].hardware.as_ref().unwrap().mac, "11:11:11:11:11:11");
+
+    let holders = index.ip_holders_at("10.1.2.3", at("13:00:00"));
+    assert_eq!(holders.len(), 1);
+    assert_eq!(holders[0].hardware.as_ref().unwrap().mac, "33:33:33:33:33:33");
+
+    assert!(index.ip_holders_at("10.1.2.3", at("12:15:00")).is_empty());
+    assert!(index.ip_holders_at("10.9.9.9", at("12:15:00")).is_empty());
+
+    // `starts` is inclusive, `ends` is exclusive
+    assert_eq!(index.ip_holders_at("10.1.2.3", at("10:00:00")).len(), 1);
+    assert_eq!(index.ip_holders_at("10.1.2.3", at("11:59:59")).len(), 1);
+    assert!(index.ip_holders_at("10.1.2.3", at("12:00:00")).is_empty());
+    for l in index.active_at(at("12:00:00")) {
+        assert!(l.is_active_at(at("12:00:00")));
+    }
+}
+
+#[test]
+fn mac_ips_during_test() {
+    let leases = parser::parse(JOURNAL).unwrap().leases;
+    let index = LeaseIndex::new(&leases);
+
+    assert_eq!(
+        index.mac_ips_during("11:11:11:11:11:11", at("00:00:00"), at("23:59:59")),
+        vec!["10.1.2.3", "10.1.2.5"]
+    );
+    assert_eq!(
+        index.mac_ips_during("11:11:11:11:11:11", at("12:01:00"), at("15:59:00")),
+        Vec::<&str>::new()
+    );
+    assert_eq!(
+        index
+            .mac_leases_during("11:11:11:11:11:11", at("17:00:00"), at("17:00:00"))
+            .len(),
+        1
+    );
+
+    // hardware addresses are matched case-insensitively
+    let leases = parser::parse("lease 10.0.0.1 { hardware ethernet 1a:2b:3c:4d:5e:6F; }")
+        .unwrap()
+        .leases;
+    let index = LeaseIndex::new(&leases);
+    assert_eq!(
+        index.mac_ips_during("1A:2B:3C:4D:5E:6f", at("00:00:00"), at("23:59:59")),
+        vec!["10.0.0.1"]
+    );
+}
+
+#[test]
+fn overlapping_test() {
+    let leases = parser::parse(JOURNAL).unwrap().leases;
+    let index = LeaseIndex::new(&leases);
+
+    let ips: Vec<&str> = index
+        .overlapping(at("11:30:00"), at("13:00:00"))
+        .iter()
+        .map(|l| l.ip.as_str())
+        .collect();
+    assert_eq!(ips, vec!["10.1.2.3", "10.1.2.4", "10.1.2.3"]);
+
+    // lease without `ends` is active indefinitely
+    let active = index.active_at(Date::from("0", "2030/01/06", "00:00:00").unwrap());
+    assert_eq!(active.len(), 1);
+    assert_eq!(active[0].ip, "10.1.2.6");
+}
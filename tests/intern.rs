@@ -0,0 +1,166 @@
+extern crate dhcpd_parser;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use crate::dhcpd_parser::common::Date;
+use crate::dhcpd_parser::intern::{parse_interned, InternedLeases};
+use crate::dhcpd_parser::parser;
+
+/// Counts the bytes allocated by the current thread, so tests running in
+/// parallel do not disturb each other
+struct Counting;
+
+thread_local! {
+    static ALLOCATED: Cell<isize> = const { Cell::new(0) };
+    static PEAK: Cell<isize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.with(|a| a.set(a.get() + layout.size() as isize));
+        PEAK.with(|p| p.set(p.get().max(ALLOCATED.with(|a| a.get()))));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.with(|a| a.set(a.get() - layout.size() as isize));
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// Returns the value and the number of bytes it keeps allocated
+fn retained<T, F: FnOnce() -> T>(f: F) -> (T, isize) {
+    let before = ALLOCATED.with(|a| a.get());
+    let value = f();
+    let after = ALLOCATED.with(|a| a.get());
+
+    (value, after - before)
+}
+
+/// Returns the value and the largest number of bytes allocated at once while
+/// computing it
+fn peak<T, F: FnOnce() -> T>(f: F) -> (T, isize) {
+    let before = ALLOCATED.with(|a| a.get());
+    PEAK.with(|p| p.set(before));
+    let value = f();
+
+    (value, PEAK.with(|p| p.get()) - before)
+}
+
+const JOURNAL: &str = "
+    lease 10.0.0.2 {
+        starts 2 2019/01/01 10:00:00;
+        ends 2 2019/01/01 12:00:00;
+        hardware ethernet 11:11:11:11:11:11;
+        uid 01:11:11:11:11:11:11;
+        client-hostname \"phone\";
+    }
+
+    lease 10.0.0.2 {
+        starts 2 2019/01/01 11:00:00;
+        ends 2 2019/01/01 13:00:00;
+        hardware ethernet 11:11:11:11:11:11;
+        uid 01:11:11:11:11:11:11;
+        client-hostname \"phone\";
+    }
+
+    lease 10.0.0.3 {
+        starts 2 2019/01/01 11:00:00;
+        abandoned;
+    }
+";
+
+#[test]
+fn interned_leases_test() {
+    let leases = parser::parse(JOURNAL).unwrap().leases;
+    let interned = InternedLeases::from_leases(&leases);
+
+    assert_eq!(interned.len(), 3);
+    // 2 IPs, hardware type, MAC, uid and hostname
+    assert_eq!(interned.interner().len(), 6);
+
+    let first = interned.get(0).unwrap();
+    let second = interned.get(1).unwrap();
+    assert_eq!(first.ip(), "10.0.0.2");
+    assert_eq!(first.mac(), Some("11:11:11:11:11:11"));
+    assert_eq!(first.hardware_type(), Some("ethernet"));
+    assert_eq!(first.client_hostname(), Some("phone"));
+    assert_eq!(first.hostname(), None);
+    assert_eq!(first.mac_symbol(), second.mac_symbol());
+    assert!(first.is_active_at(Date::from("2", "2019/01/01", "10:30:00").unwrap()));
+    assert!(!first.is_active_at(Date::from("2", "2019/01/01", "12:30:00").unwrap()));
+
+    let third = interned.get(2).unwrap();
+    assert!(third.abandoned());
+    assert_eq!(third.mac(), None);
+    assert!(interned.get(3).is_none());
+
+    assert_eq!(interned.to_leases(), leases);
+    assert_eq!(
+        interned
+            .interner()
+            .get("phone")
+            .map(|s| interned.interner().resolve(s)),
+        Some("phone")
+    );
+}
+
+#[test]
+fn parse_interned_test() {
+    let interned = parse_interned(JOURNAL).unwrap();
+
+    assert_eq!(interned.len(), 3);
+    assert_eq!(interned.interner().len(), 6);
+    assert_eq!(interned.to_leases(), parser::parse(JOURNAL).unwrap().leases);
+    assert!(parse_interned("lease 10.0.0.2 { starts 2 2019/13;").is_err());
+    assert!(parse_interned("lease 10.0.0.2 { } }").is_err());
+    assert!(parse_interned("lease 10.0.0.2 { } lease").is_err());
+}
+
+#[test]
+fn interned_memory_test() {
+    let mut journal = String::new();
+    for n in 0..2000 {
+        let client = n % 50;
+        journal.push_str(&format!(
+            "lease 10.0.0.{} {{
+                starts 2 2019/01/01 {:02}:00:00;
+                ends 2 2019/01/01 {:02}:30:00;
+                hardware ethernet 02:00:00:00:00:{:02x};
+                uid \"\\001\\002\\000\\000\\000\\000\\000{:02}\";
+                client-hostname \"client-{}\";
+            }}\n",
+            client,
+            n % 24,
+            n % 24,
+            client,
+            client,
+            client
+        ));
+    }
+
+    let (leases, parsed) = retained(|| parser::parse(journal.as_str()).unwrap().leases);
+    let (interned, compact) = retained(|| parse_interned(journal.as_str()).unwrap());
+
+    assert_eq!(interned.to_leases(), leases);
+    assert!(
+        compact * 3 < parsed,
+        "interned leases take {} bytes, parsed leases {}",
+        compact,
+        parsed
+    );
+
+    // the items of the whole input are never held at once
+    let (_, parsing) = peak(|| parser::parse(journal.as_str()).unwrap());
+    let (_, interning) = peak(|| parse_interned(journal.as_str()).unwrap());
+    assert!(
+        interning * 2 < parsing,
+        "interning takes {} bytes at most, parsing {}",
+        interning,
+        parsing
+    );
+}
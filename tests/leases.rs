@@ -1,6 +1,7 @@
 extern crate dhcpd_parser;
 
 use crate::dhcpd_parser::common::Date;
+use crate::dhcpd_parser::net::Prefix;
 use crate::dhcpd_parser::parser;
 use crate::dhcpd_parser::parser::LeasesMethods;
 
@@ -16,6 +17,21 @@ fn basic_test() {
     assert!(res.is_ok());
 }
 
+#[test]
+fn crlf_test() {
+    let input = "lease 192.0.0.2 {\r\n\
+                 \tstarts 2 2019/01/01 22:00:00;\r\n\
+                 \thardware ethernet 11:11:11:11:11:11;\r\n\
+                 \tclient-hostname \"laptop\";\r\n\
+                 }\r\n";
+    let leases = parser::parse(input).unwrap().leases;
+    assert_eq!(leases, parser::parse(input.replace('\r', "")).unwrap().leases);
+    assert_eq!(leases[0].hardware.as_ref().unwrap().mac, "11:11:11:11:11:11");
+
+    // other whitespace separates words as well
+    assert!(parser::parse("lease 192.0.0.2\u{b}{\u{c}}\u{a0}").is_ok());
+}
+
 #[test]
 fn dates_test() {
     let res = parser::parse(
@@ -132,27 +148,18 @@ fn is_active_test() {
 
     assert!(leases[0].is_active_at(Date::from("2", "2019/01/01", "22:30:00").unwrap()));
 
-    assert_eq!(
-        leases[1].is_active_at(Date::from("1", "1985/01/01", "22:30:00").unwrap()),
-        false
-    );
+    assert!(!leases[1].is_active_at(Date::from("1", "1985/01/01", "22:30:00").unwrap()));
 
-    assert_eq!(
-        leases[0].is_active_at(Date::from("2", "2019/01/01", "21:59:00").unwrap()),
-        false
-    );
+    assert!(!leases[0].is_active_at(Date::from("2", "2019/01/01", "21:59:00").unwrap()));
 
-    assert_eq!(
-        leases[0].is_active_at(
-            Date::from(
-                "2".to_string(),
-                "2019/01/01".to_string(),
-                "23:59:00".to_string()
-            )
-            .unwrap()
-        ),
-        false
-    );
+    assert!(!leases[0].is_active_at(
+        Date::from(
+            "2".to_string(),
+            "2019/01/01".to_string(),
+            "23:59:00".to_string()
+        )
+        .unwrap()
+    ));
 }
 
 #[test]
@@ -229,3 +236,58 @@ fn client_hostnames_test() {
             .collect()
     );
 }
+
+#[test]
+fn malformed_input_is_error_test() {
+    assert!(parser::parse("lease").is_err());
+    assert!(parser::parse("lease 192.0.0.2").is_err());
+    assert!(parser::parse("lease 192.0.0.2 starts").is_err());
+    assert!(parser::parse("lease 192.0.0.2 { starts 2 2019/01/01").is_err());
+    assert!(parser::parse("lease 192.0.0.2 { starts x 2019/01/01 00:00:00; }").is_err());
+    assert!(parser::parse("lease 192.0.0.2 { starts 2 2019/xx/01 00:00:00; }").is_err());
+    assert!(parser::parse("lease 192.0.0.2 { ends 2 2019/01/01 00:61:00; }").is_err());
+    assert!(parser::parse("lease 192.0.0.2 { ends 4 99999999999999/01/01 00:00:00; }").is_err());
+    assert!(parser::parse("lease 192.0.0.2 { hardware ethernet").is_err());
+}
+
+#[test]
+fn subnets_test() {
+    let leases = parser::parse(
+        "
+    lease 10.20.1.2 {}
+    lease 10.20.2.3 {}
+    lease 10.30.0.4 {}
+    lease fd00::5 {}
+    lease localhost {}
+    "
+        .to_string(),
+    )
+    .unwrap()
+    .leases;
+    let ips = |ls: &dhcpd_parser::leases::Leases| -> Vec<String> {
+        ls.iter().map(|l| l.ip.clone()).collect()
+    };
+
+    let wide = Prefix::from("10.20.0.0/16").unwrap();
+    let narrow = Prefix::from("10.20.2.0/24").unwrap();
+    let v6 = Prefix::from("fd00::/8").unwrap();
+    let empty = Prefix::from("192.168.0.0/24").unwrap();
+
+    let inside = leases.in_subnets(&[wide, v6]);
+    assert_eq!(ips(&inside), vec!["10.20.1.2", "10.20.2.3", "fd00::5"]);
+
+    let groups = leases.group_by_subnet(&[wide, narrow, v6, empty]);
+    let groups: Vec<(String, Vec<String>)> = groups
+        .iter()
+        .map(|(p, ls)| (p.to_string(), ips(ls)))
+        .collect();
+    assert_eq!(
+        groups,
+        vec![
+            ("10.20.0.0/16".to_owned(), vec!["10.20.1.2".to_owned()]),
+            ("10.20.2.0/24".to_owned(), vec!["10.20.2.3".to_owned()]),
+            ("fd00::/8".to_owned(), vec!["fd00::5".to_owned()]),
+            ("192.168.0.0/24".to_owned(), vec![]),
+        ]
+    );
+}
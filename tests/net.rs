@@ -0,0 +1,102 @@
+extern crate dhcpd_parser;
+
+use std::net::IpAddr;
+
+use crate::dhcpd_parser::net::{AddressRange, Prefix};
+
+#[test]
+fn prefix_from_test() {
+    let p = Prefix::from("192.168.1.77/24").unwrap();
+    assert_eq!(p.to_string(), "192.168.1.0/24");
+    assert_eq!(p.prefix_len(), 24);
+    assert_eq!(p.addr(), "192.168.1.0".parse::<IpAddr>().unwrap());
+
+    assert_eq!(Prefix::from("10.0.0.1").unwrap().to_string(), "10.0.0.1/32");
+    assert_eq!(Prefix::from("0.0.0.0/0").unwrap().to_string(), "0.0.0.0/0");
+    assert_eq!(Prefix::from("fd00::1/8").unwrap().to_string(), "fd00::/8");
+
+    assert!(Prefix::from("10.0.0.0/33").is_err());
+    assert!(Prefix::from("10.0.0/8").is_err());
+    assert!(Prefix::from("10.0.0.0/x").is_err());
+}
+
+#[test]
+fn prefix_contains_test() {
+    let p = Prefix::from("10.0.0.0/23").unwrap();
+    assert!(p.contains_str("10.0.0.1"));
+    assert!(p.contains_str("10.0.1.255"));
+    assert!(!p.contains_str("10.0.2.0"));
+    assert!(!p.contains_str("::1"));
+    assert!(!p.contains_str("localhost"));
+
+    assert!(Prefix::from("0.0.0.0/0").unwrap().contains_str("8.8.8.8"));
+    assert!(Prefix::from("2001:db8::/32")
+        .unwrap()
+        .contains_str("2001:db8:1::5"));
+}
+
+#[test]
+fn address_range_test() {
+    let r = AddressRange::from("10.0.0.10 - 10.0.0.200").unwrap();
+    assert_eq!(r.to_string(), "10.0.0.10-10.0.0.200");
+    assert_eq!(r.size(), 191);
+    assert!(r.contains_str("10.0.0.10"));
+    assert!(r.contains_str("10.0.0.200"));
+    assert!(!r.contains_str("10.0.0.201"));
+    assert!(!r.contains_str("::a"));
+
+    let r = AddressRange::from("10.0.0.77/24").unwrap();
+    assert_eq!(r.to_string(), "10.0.0.0-10.0.0.255");
+    assert_eq!(r, Prefix::from("10.0.0.0/24").unwrap().range());
+    assert_eq!(AddressRange::from("10.0.0.1").unwrap().size(), 1);
+    assert_eq!(AddressRange::from("::/0").unwrap().size(), u128::MAX);
+
+    assert!(AddressRange::from("10.0.0.2-10.0.0.1").is_err());
+    assert!(AddressRange::from("10.0.0.1-::2").is_err());
+    assert!(AddressRange::from("10.0.0.1-x").is_err());
+}
+
+#[test]
+fn prefix_hosts_test() {
+    let p = Prefix::from("192.168.1.0/30").unwrap();
+    assert_eq!(p.size(), 4);
+    assert_eq!(p.host_count(), 2);
+    let hosts: Vec<String> = p.hosts().map(|ip| ip.to_string()).collect();
+    assert_eq!(hosts, vec!["192.168.1.1", "192.168.1.2"]);
+
+    // point-to-point links and single hosts have no network address
+    assert_eq!(Prefix::from("10.0.0.0/31").unwrap().hosts().count(), 2);
+    assert_eq!(Prefix::from("10.0.0.1/32").unwrap().host_count(), 1);
+
+    assert_eq!(Prefix::from("10.0.0.0/16").unwrap().host_count(), 65534);
+    assert_eq!(
+        Prefix::from("10.0.0.0/8").unwrap().hosts().size_hint().0,
+        16777214
+    );
+    assert_eq!(Prefix::from("fd00::/120").unwrap().host_count(), 256);
+    assert_eq!(Prefix::from("::/0").unwrap().size(), u128::MAX);
+    assert_eq!(
+        Prefix::from("::/0").unwrap().hosts().size_hint(),
+        (usize::MAX, None)
+    );
+
+    let last: Vec<IpAddr> = Prefix::from("ffff:ffff:ffff:ffff:ffff:ffff:ffff:fffe/127")
+        .unwrap()
+        .hosts()
+        .collect();
+    assert_eq!(last.len(), 2);
+}
+
+#[test]
+fn address_range_iter_test() {
+    let r = AddressRange::from("10.0.0.254-10.0.1.1").unwrap();
+    let ips: Vec<String> = r.iter().map(|ip| ip.to_string()).collect();
+    assert_eq!(
+        ips,
+        vec!["10.0.0.254", "10.0.0.255", "10.0.1.0", "10.0.1.1"]
+    );
+    assert_eq!(r.iter().size_hint(), (4, Some(4)));
+
+    let all = AddressRange::from("::-ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff").unwrap();
+    assert_eq!(all.iter().size_hint(), (usize::MAX, None));
+}
@@ -0,0 +1,142 @@
+extern crate dhcpd_parser;
+
+use std::env;
+use std::fs;
+use std::process;
+
+use crate::dhcpd_parser::leases::{Hardware, Lease};
+use crate::dhcpd_parser::oui::OuiDb;
+
+const TXT: &str = "OUI/MA-L\t\t\tOrganization
+company_id\t\t\tOrganization
+\t\t\t\tAddress
+
+00-00-0C   (hex)\t\tCisco Systems, Inc
+00000C     (base 16)\t\tCisco Systems, Inc
+\t\t\t\t170 West Tasman Drive
+\t\t\t\tSan Jose  CA  95134
+\t\t\t\tUS
+
+08-00-27   (hex)\t\tPCS Systemtechnik GmbH
+080027     (base 16)\t\tPCS Systemtechnik GmbH
+";
+
+const CSV: &str = "Registry,Assignment,Organization Name,Organization Address
+MA-L,00000C,\"Cisco Systems, Inc\",170 West Tasman Drive San Jose CA US 95134
+MA-M,70B3D51,\"Fancy \"\"Sensors\"\" Ltd\",Somewhere
+MA-L,70B3D5,IEEE Registration Authority,445 Hoes Lane Piscataway NJ US 08554
+";
+
+const MAM_TXT: &str = "MA-M\t\t\tOrganization
+company_id\t\t\tOrganization
+\t\t\t\tAddress
+
+70-B3-D5   (hex)\t\tFancy Sensors Ltd
+100000-1FFFFF     (base 16)\t\tFancy Sensors Ltd
+\t\t\t\tSomewhere
+
+70-B3-D5   (hex)\t\tTiny Devices
+70B3D5000000-70B3D5000FFF     (base 16)\t\tTiny Devices
+\t\t\t\tElsewhere
+";
+
+#[test]
+fn txt_test() {
+    let db = OuiDb::from(TXT).unwrap();
+    assert_eq!(db.len(), 2);
+    assert_eq!(db.lookup("00:00:0c:12:34:56"), Some("Cisco Systems, Inc"));
+    assert_eq!(
+        db.lookup("08-00-27-12-34-56"),
+        Some("PCS Systemtechnik GmbH")
+    );
+    assert_eq!(db.lookup("00:00:0d:12:34:56"), None);
+    assert_eq!(db.lookup("00:00"), None);
+}
+
+#[test]
+fn csv_test() {
+    let db = OuiDb::from(CSV).unwrap();
+    assert_eq!(db.len(), 3);
+    assert_eq!(db.lookup("00:00:0C:00:00:01"), Some("Cisco Systems, Inc"));
+    // the longest assignment wins
+    assert_eq!(
+        db.lookup("70:b3:d5:12:34:56"),
+        Some("Fancy \"Sensors\" Ltd")
+    );
+    assert_eq!(
+        db.lookup("70:b3:d5:22:34:56"),
+        Some("IEEE Registration Authority")
+    );
+
+    assert!(OuiDb::from("MA-L,00000C").is_err());
+    assert!(OuiDb::from("MA-L,00000X,Nobody,Nowhere").is_err());
+}
+
+#[test]
+fn insert_test() {
+    let mut db = OuiDb::new();
+    assert!(db.is_empty());
+    db.insert("52:54:00", "QEMU").unwrap();
+    assert_eq!(db.lookup("52:54:00:ab:cd:ef"), Some("QEMU"));
+
+    assert!(db.insert("52:54", "QEMU").is_err());
+    assert!(db.insert("52:54:0g", "QEMU").is_err());
+}
+
+#[test]
+fn load_test() {
+    let path = env::temp_dir().join(format!("dhcpd-parser-oui-{}", process::id()));
+    fs::write(&path, CSV).unwrap();
+    let db = OuiDb::load(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(db.len(), 3);
+
+    assert!(OuiDb::load(&path).is_err());
+}
+
+#[test]
+fn vendor_test() {
+    let db = OuiDb::from(CSV).unwrap();
+    let mut lease = Lease::new();
+    assert_eq!(lease.vendor(&db), None);
+
+    lease.hardware = Some(Hardware {
+        h_type: "ethernet".to_owned(),
+        mac: "00:00:0c:00:00:01".to_owned(),
+    });
+    assert_eq!(lease.vendor(&db), Some("Cisco Systems, Inc"));
+}
+
+#[cfg(feature = "oui-snapshot")]
+#[test]
+fn snapshot_test() {
+    let db = OuiDb::snapshot();
+    assert!(!db.is_empty());
+    assert_eq!(
+        db.lookup("b8:27:eb:00:00:01"),
+        Some("Raspberry Pi Foundation")
+    );
+}
+
+#[test]
+fn ranges_txt_test() {
+    let db = OuiDb::from(MAM_TXT).unwrap();
+    assert_eq!(db.len(), 2);
+    assert_eq!(db.lookup("70:b3:d5:12:34:56"), Some("Fancy Sensors Ltd"));
+    assert_eq!(db.lookup("70:b3:d5:00:0a:bc"), Some("Tiny Devices"));
+    // the block itself is not assigned to either
+    assert_eq!(db.lookup("70:b3:d5:22:34:56"), None);
+    assert_eq!(db.lookup("70:b3:d5:00:1a:bc"), None);
+
+    for range in [
+        "100000-1FFFFE",
+        "100001-200000",
+        "70B3D6000000-70B3D6000FFF",
+        "1000-1FFF",
+    ]
+    .iter()
+    {
+        let txt = format!("70-B3-D5   (hex)\t\tX\n{}     (base 16)\t\tX\n", range);
+        assert!(OuiDb::from(&txt).is_err(), "{}", range);
+    }
+}
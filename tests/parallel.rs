@@ -0,0 +1,63 @@
+#![cfg(feature = "rayon")]
+
+extern crate dhcpd_parser;
+
+use std::env;
+use std::fs;
+use std::process;
+
+use crate::dhcpd_parser::parallel;
+use crate::dhcpd_parser::parser;
+
+fn journal(count: usize) -> String {
+    let mut res = String::new();
+    for i in 0..count {
+        res.push_str(&format!(
+            "lease 10.{}.{}.{} {{\n\tstarts 2 2019/01/01 10:{:0>2}:00;\n\thardware ethernet 11:11:11:11:{:0>2x}:{:0>2x};\n\tclient-hostname \"host{{{}}}\";\n}}\n",
+            i / 65536,
+            i / 256 % 256,
+            i % 256,
+            i % 60,
+            i / 256 % 256,
+            i % 256,
+            i,
+        ));
+    }
+
+    res
+}
+
+#[test]
+fn parse_parallel_test() {
+    let input = journal(20000);
+
+    let sequential = parser::parse(input.as_str()).unwrap();
+    let parallel = parallel::parse_parallel(&input).unwrap();
+
+    assert_eq!(parallel.leases.len(), 20000);
+    assert_eq!(parallel, sequential);
+}
+
+#[test]
+fn parse_parallel_error_test() {
+    let input = format!(
+        "{}lease 10.0.0.1 {{ starts;\n}}\n{}",
+        journal(5000),
+        journal(5000)
+    );
+    assert!(parallel::parse_parallel(&input).is_err());
+}
+
+#[test]
+fn parse_path_parallel_test() {
+    let dir = env::temp_dir().join(format!("dhcpd-parser-parallel-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("dhcpd.leases.1");
+    fs::write(&path, journal(1000)).unwrap();
+
+    let res = parallel::parse_path_parallel(&path).unwrap();
+    assert_eq!(res.leases.len(), 1000);
+    assert_eq!(res.leases[999].ip, "10.0.3.231");
+
+    fs::remove_dir_all(&dir).unwrap();
+}
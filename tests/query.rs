@@ -0,0 +1,175 @@
+extern crate dhcpd_parser;
+
+use crate::dhcpd_parser::common::Date;
+use crate::dhcpd_parser::export::Column;
+use crate::dhcpd_parser::leases::Leases;
+use crate::dhcpd_parser::net::Prefix;
+use crate::dhcpd_parser::parser;
+use crate::dhcpd_parser::query::LeaseFilter;
+
+fn leases() -> Leases {
+    parser::parse(
+        "
+        lease 10.0.0.2 {
+            starts 2 2019/01/01 10:00:00;
+            ends 2 2019/01/01 12:00:00;
+            hardware ethernet 00:1a:2b:00:00:01;
+            client-hostname \"laptop-1\";
+        }
+
+        lease 10.0.3.3 {
+            starts 2 2019/01/01 10:00:00;
+            ends 2 2019/01/01 12:00:00;
+            hardware ethernet 00:1a:2b:00:00:02;
+            abandoned;
+        }
+
+        lease 10.0.4.4 {
+            starts 2 2019/01/01 11:00:00;
+            ends 2 2019/01/01 13:00:00;
+            hardware ethernet 11:22:33:44:55:66;
+            uid \"printer\";
+            hostname \"printer\";
+        }
+        ",
+    )
+    .unwrap()
+    .leases
+}
+
+fn ips(query: &str) -> Vec<String> {
+    let filter = LeaseFilter::from(query).unwrap();
+    filter
+        .filter(&leases())
+        .iter()
+        .map(|l| l.ip.clone())
+        .collect()
+}
+
+#[test]
+fn query_test() {
+    assert_eq!(
+        ips("mac ~ \"^00:1a\" and active at \"2019-01-01T11:00:00Z\" and not abandoned and ip in 10.0.0.0/22"),
+        ["10.0.0.2"]
+    );
+    assert_eq!(ips("mac = 00:1A:2B:00:00:02"), ["10.0.3.3"]);
+    assert_eq!(ips("hostname != printer"), ["10.0.0.2", "10.0.3.3"]);
+    assert_eq!(ips("client-hostname ~ laptop"), ["10.0.0.2"]);
+    assert_eq!(ips("uid !~ ."), ["10.0.0.2", "10.0.3.3"]);
+    assert_eq!(
+        ips("abandoned or hostname = printer"),
+        ["10.0.3.3", "10.0.4.4"]
+    );
+    assert_eq!(ips("not (abandoned or hostname = printer)"), ["10.0.0.2"]);
+    assert_eq!(ips("starts >= 2019-01-01T11:00:00Z"), ["10.0.4.4"]);
+    assert_eq!(
+        ips("ends < 2019-01-01T13:00:00Z and not abandoned"),
+        ["10.0.0.2"]
+    );
+    assert_eq!(ips("active at 2019-01-01T12:30:00Z"), ["10.0.4.4"]);
+}
+
+#[test]
+fn pattern_test() {
+    assert_eq!(ips("mac ~ \"^00:1a:2b:00:00:0[2-9]\""), ["10.0.3.3"]);
+    assert_eq!(ips("mac ~ \"(01|02)$\""), ["10.0.0.2", "10.0.3.3"]);
+    assert_eq!(
+        ips("ip ~ \"^10\\\\.0\\\\.[34]\\\\.\\\\d+$\""),
+        ["10.0.3.3", "10.0.4.4"]
+    );
+    assert_eq!(ips("client-hostname ~ \"^\\\\w+-?[^a-z]$\""), ["10.0.0.2"]);
+    assert_eq!(ips("mac ~ \"^(\\\\d\\\\d:)+55\""), ["10.0.4.4"]);
+    assert_eq!(ips("hostname ~ \"prin?ter\""), ["10.0.4.4"]);
+    assert_eq!(ips("hostname ~ \"^$\""), Vec::<String>::new());
+}
+
+#[test]
+fn builder_test() {
+    let date = Date::from("2", "2019/01/01", "11:00:00").unwrap();
+    let filter = LeaseFilter::matches(Column::Mac, "^00:1a")
+        .unwrap()
+        .and(LeaseFilter::active_at(date))
+        .and(!LeaseFilter::abandoned())
+        .and(LeaseFilter::ip_in(Prefix::from("10.0.0.0/22").unwrap()));
+
+    assert_eq!(
+        filter,
+        LeaseFilter::from(
+            "mac ~ ^00:1a and active at 2019-01-01T11:00:00Z and not abandoned and ip in 10.0.0.0/22"
+        )
+        .unwrap()
+    );
+    let selected = filter.filter(&leases());
+    assert_eq!(selected.len(), 1);
+    assert!(filter.is_match(&selected[0]));
+
+    assert_eq!(LeaseFilter::any().filter(&leases()), leases());
+    assert!(LeaseFilter::before(Column::Ip, date).is_err());
+    assert!(LeaseFilter::equals(Column::State, "active").is_err());
+}
+
+#[test]
+fn query_error_test() {
+    for query in [
+        "",
+        "mac",
+        "mac ~",
+        "vendor = x",
+        "state = active",
+        "ip < 10.0.0.1",
+        "starts > yesterday",
+        "active 2019-01-01T11:00:00Z",
+        "ip in 10.0.0.0/40",
+        "(abandoned",
+        "abandoned)",
+        "hostname = \"printer",
+        "mac ~ \"(00\"",
+        "mac ~ \"*\"",
+        "mac ~ \"[b-a]\"",
+        "abandoned ! abandoned",
+    ]
+    .iter()
+    {
+        assert!(LeaseFilter::from(query).is_err(), "{}", query);
+    }
+}
+
+#[test]
+fn nesting_limit_test() {
+    let nested = |open: &str, inner: &str, close: &str, n: usize| {
+        format!("{}{}{}", open.repeat(n), inner, close.repeat(n))
+    };
+
+    assert_eq!(ips(&nested("(", "abandoned", ")", 10)), ["10.0.3.3"]);
+    assert_eq!(ips(&nested("not ", "abandoned", "", 10)), ["10.0.3.3"]);
+    assert_eq!(
+        ips(&format!("mac ~ \"{}\"", nested("(", "55", ")", 10))),
+        ["10.0.4.4"]
+    );
+
+    for &n in [100, 100_000].iter() {
+        assert!(LeaseFilter::from(&nested("(", "abandoned", ")", n)).is_err());
+        assert!(LeaseFilter::from(&nested("not ", "abandoned", "", n)).is_err());
+        let pattern = format!("mac ~ \"{}\"", nested("(", "a", ")", n));
+        assert!(LeaseFilter::from(&pattern).is_err());
+    }
+}
+
+#[test]
+fn long_query_test() {
+    let n = 20_000;
+
+    let query = vec!["not abandoned"; n].join(" and ");
+    assert_eq!(ips(&query).len(), leases().len() - 1);
+    let query = vec!["abandoned"; n].join(" or ");
+    assert_eq!(ips(&query), ["10.0.3.3"]);
+
+    let filter = (0..n).fold(LeaseFilter::any(), |f, _| f.and(!LeaseFilter::abandoned()));
+    assert_eq!(filter.filter(&leases()).len(), leases().len() - 1);
+
+    // every optional character is a split to follow without consuming input
+    let pattern = format!("mac ~ \"^{}$\"", "a?".repeat(60_000));
+    assert!(ips(&pattern).is_empty());
+    let pattern = format!("ip ~ \"{}10\"", "a?".repeat(60_000));
+    assert_eq!(ips(&pattern).len(), leases().len());
+}
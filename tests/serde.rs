@@ -0,0 +1,102 @@
+#![cfg(feature = "serde")]
+
+extern crate dhcpd_parser;
+
+use crate::dhcpd_parser::common::Date;
+use crate::dhcpd_parser::leases::LeaseKeyword;
+use crate::dhcpd_parser::parser;
+use crate::dhcpd_parser::parser::ParserResult;
+
+const LEASES: &str = "
+    lease 192.168.0.2 {
+        starts 2 2019/01/01 22:00:00 UTC;
+        ends 3 2019/01/02 22:00:00 UTC;
+        hardware ethernet 11:11:11:11:11:11;
+        uid 01:11:11:11:11:11:11;
+        client-hostname \"CLIENTHOSTNAME\";
+        hostname \"TESTHOSTNAME\";
+        abandoned;
+    }
+
+    lease 192.168.0.3 {
+    }
+";
+
+#[test]
+fn serialize_test() {
+    let res = parser::parse(LEASES).unwrap();
+
+    let json = serde_json::to_value(&res).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "leases": [
+                {
+                    "ip": "192.168.0.2",
+                    "dates": {
+                        "starts": "2019-01-01T22:00:00Z",
+                        "ends": "2019-01-02T22:00:00Z",
+                    },
+                    "hardware": {
+                        "h_type": "ethernet",
+                        "mac": "11:11:11:11:11:11",
+                    },
+                    "uid": "01:11:11:11:11:11:11",
+                    "client_hostname": "CLIENTHOSTNAME",
+                    "hostname": "TESTHOSTNAME",
+                    "abandoned": true,
+                },
+                {
+                    "ip": "192.168.0.3",
+                    "dates": {
+                        "starts": null,
+                        "ends": null,
+                    },
+                    "hardware": null,
+                    "uid": null,
+                    "client_hostname": null,
+                    "hostname": null,
+                    "abandoned": false,
+                },
+            ]
+        })
+    );
+
+    assert_eq!(
+        serde_json::to_string(&LeaseKeyword::ClientHostname).unwrap(),
+        "\"client-hostname\""
+    );
+}
+
+#[test]
+fn deserialize_test() {
+    let res = parser::parse(LEASES).unwrap();
+
+    let json = serde_json::to_string(&res).unwrap();
+    let back: ParserResult = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, res);
+
+    // weekday is computed from the date
+    let d: Date = serde_json::from_str("\"2019-03-01T00:00:00Z\"").unwrap();
+    assert_eq!(d.to_string(), "Friday 2019/03/01 00:00:00");
+    assert!(serde_json::from_str::<Date>("\"2019/03/01\"").is_err());
+}
+
+#[test]
+fn deserialize_offset_test() {
+    let date = |s: &str| serde_json::from_str::<Date>(&format!("\"{}\"", s));
+
+    let utc = date("2019-03-01T00:30:00Z").unwrap();
+    assert_eq!(date("2019-03-01T02:30:00+02:00").unwrap(), utc);
+    assert_eq!(date("2019-02-28T23:00:00-01:30").unwrap(), utc);
+    assert_eq!(date("2019-03-01T00:30:00.250z").unwrap(), utc);
+    assert_eq!(
+        date("2019-03-01T01:00:00+01:00").unwrap().to_string(),
+        "Friday 2019/03/01 00:00:00"
+    );
+
+    assert!(date("2019-03-01T00:30:00").is_err());
+    assert!(date("2019-03-01T00:30:00+0200").is_err());
+    assert!(date("2019-03-01T00:30:00+25:00").is_err());
+    assert!(date("2019-03-01T00:30:00Zulu").is_err());
+}
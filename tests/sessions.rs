@@ -0,0 +1,111 @@
+extern crate dhcpd_parser;
+
+use std::time::Duration;
+
+use crate::dhcpd_parser::common::Date;
+use crate::dhcpd_parser::leases::ClientId;
+use crate::dhcpd_parser::parser;
+use crate::dhcpd_parser::sessions::{self, SessionOptions};
+
+fn date(t: &str) -> Date {
+    Date::from("2", "2019/01/01", t).unwrap()
+}
+
+fn options() -> SessionOptions {
+    SessionOptions {
+        now: date("23:00:00"),
+        ..SessionOptions::default()
+    }
+}
+
+const JOURNAL: &str = "
+    lease 10.0.0.2 {
+        starts 2 2019/01/01 10:00:00;
+        ends 2 2019/01/01 12:00:00;
+        hardware ethernet 00:00:00:00:00:01;
+        client-hostname \"laptop\";
+    }
+    lease 10.0.0.2 {
+        starts 2 2019/01/01 11:00:00;
+        ends 2 2019/01/01 13:00:00;
+        hardware ethernet 00:00:00:00:00:01;
+        client-hostname \"laptop-2\";
+    }
+    lease 10.0.0.3 {
+        starts 2 2019/01/01 13:00:00;
+        ends 2 2019/01/01 14:00:00;
+        hardware ethernet 00:00:00:00:00:01;
+        client-hostname \"laptop\";
+    }
+    lease 10.0.0.3 {
+        starts 2 2019/01/01 15:00:00;
+        ends 2 2019/01/01 16:00:00;
+        hardware ethernet 00:00:00:00:00:01;
+    }
+    lease 10.0.0.4 {
+        starts 2 2019/01/01 20:00:00;
+        uid \"\\001\\002\";
+    }
+    lease 10.0.0.5 {
+        starts 2 2019/01/01 09:00:00;
+        ends 2 2019/01/01 10:00:00;
+    }
+";
+
+#[test]
+fn sessions_test() {
+    let leases = parser::parse(JOURNAL).unwrap().leases;
+    let devices = sessions::devices(&leases, &options());
+    assert_eq!(devices.len(), 2);
+
+    let laptop = &devices[0];
+    assert_eq!(laptop.client, ClientId::MAC("00:00:00:00:00:01".to_owned()));
+    assert_eq!(laptop.sessions.len(), 3);
+
+    let renewed = &laptop.sessions[0];
+    assert_eq!(renewed.ip, "10.0.0.2");
+    assert_eq!(renewed.first_seen, date("10:00:00"));
+    assert_eq!(renewed.last_seen, date("13:00:00"));
+    assert_eq!(renewed.connected, Duration::from_secs(3 * 3600));
+    assert_eq!(renewed.hostnames, vec!["laptop", "laptop-2"]);
+    assert_eq!(renewed.leases, vec![0, 1]);
+
+    // the address changed, then a gap
+    assert_eq!(laptop.sessions[1].ip, "10.0.0.3");
+    assert_eq!(laptop.sessions[1].leases, vec![2]);
+    assert_eq!(laptop.sessions[2].leases, vec![3]);
+    assert!(laptop.sessions[2].hostnames.is_empty());
+
+    assert_eq!(laptop.first_seen(), Some(date("10:00:00")));
+    assert_eq!(laptop.last_seen(), Some(date("16:00:00")));
+    assert_eq!(laptop.connected(), Duration::from_secs(5 * 3600));
+    assert_eq!(laptop.hostnames(), vec!["laptop", "laptop-2"]);
+
+    // without ends the session lasts until now
+    let phone = &devices[1];
+    assert_eq!(phone.sessions.len(), 1);
+    assert_eq!(phone.sessions[0].last_seen, date("23:00:00"));
+    assert_eq!(phone.connected(), Duration::from_secs(3 * 3600));
+}
+
+#[test]
+fn max_gap_test() {
+    let leases = parser::parse(JOURNAL).unwrap().leases;
+    let mac = ClientId::MAC("00:00:00:00:00:01".to_owned());
+    let opts = SessionOptions {
+        max_gap: Duration::from_secs(3600),
+        ..options()
+    };
+
+    let laptop = sessions::device(&leases, &mac, &opts);
+    assert_eq!(laptop.sessions.len(), 2);
+    assert_eq!(laptop.sessions[1].leases, vec![2, 3]);
+    assert_eq!(laptop.sessions[1].first_seen, date("13:00:00"));
+    assert_eq!(laptop.sessions[1].last_seen, date("16:00:00"));
+    assert_eq!(laptop.sessions[1].connected, Duration::from_secs(2 * 3600));
+
+    let unknown = ClientId::MAC("00:00:00:00:00:09".to_owned());
+    assert!(sessions::device(&leases, &unknown, &opts)
+        .sessions
+        .is_empty());
+}
@@ -0,0 +1,93 @@
+extern crate dhcpd_parser;
+
+use std::env;
+use std::fs;
+use std::process;
+use std::time::{Duration, UNIX_EPOCH};
+
+use crate::dhcpd_parser::parser;
+use crate::dhcpd_parser::snapshot::{self, SourceInfo};
+
+const LEASES: &str = "
+    lease 192.168.0.2 {
+        starts 2 2019/01/01 22:00:00 UTC;
+        ends 2 2019/01/01 22:00:00 UTC;
+        hardware type 11:11:11:11:11:11;
+        uid Client1;
+        client-hostname \"CLIENTHOSTNAME\";
+        hostname \"TESTHOSTNAME\";
+        abandoned;
+    }
+
+    lease 192.168.0.3 {
+        starts 1 1985/01/01 00:00:00 UTC;
+        hardware type 11:11:11:11:11:11;
+        hostname \"TESTHOSTNAME\";
+    }
+
+    lease 192.168.0.2 {
+    }
+";
+
+#[test]
+fn save_load_test() {
+    let leases = parser::parse(LEASES).unwrap().leases;
+    let source = SourceInfo {
+        size: 1234,
+        modified: UNIX_EPOCH + Duration::new(1546380000, 123),
+    };
+
+    let mut buf = Vec::new();
+    snapshot::save(&mut buf, &leases, &source).unwrap();
+    assert!(buf.starts_with(b"DHCPDLS\0\x01\0\0\0"));
+
+    let (loaded_source, loaded) = snapshot::load(&mut buf.as_slice()).unwrap();
+    assert_eq!(loaded_source, source);
+    assert_eq!(loaded, leases);
+    assert_eq!(
+        snapshot::read_source_info(&mut buf.as_slice()).unwrap(),
+        source
+    );
+
+    // repeated strings are stored once
+    let text = String::from_utf8_lossy(&buf);
+    assert_eq!(text.matches("11:11:11:11:11:11").count(), 1);
+    assert_eq!(text.matches("TESTHOSTNAME").count(), 1);
+
+    assert!(snapshot::load(&mut &buf[..buf.len() - 1]).is_err());
+    buf[8] = 2;
+    assert_eq!(
+        snapshot::load(&mut buf.as_slice()).unwrap_err(),
+        "Unsupported snapshot version 2, expected 1"
+    );
+}
+
+#[test]
+fn load_cached_test() {
+    let dir = env::temp_dir().join(format!("dhcpd-parser-snapshot-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let source = dir.join("dhcpd.leases");
+    let cache = dir.join("dhcpd.leases.snapshot");
+    fs::write(&source, LEASES).unwrap();
+
+    let leases = snapshot::load_cached(&source, &cache).unwrap();
+    assert_eq!(leases, parser::parse(LEASES).unwrap().leases);
+    let info = snapshot::read_source_info(&mut fs::File::open(&cache).unwrap()).unwrap();
+    assert_eq!(info, SourceInfo::from_path(&source).unwrap());
+
+    // an up to date snapshot is used without looking at the source
+    let mut other = Vec::new();
+    let other_leases = parser::parse("lease 10.0.0.1 { }").unwrap().leases;
+    snapshot::save(&mut other, &other_leases, &info).unwrap();
+    fs::write(&cache, other).unwrap();
+    assert_eq!(
+        snapshot::load_cached(&source, &cache).unwrap(),
+        other_leases
+    );
+
+    // changed source invalidates the snapshot
+    fs::write(&source, format!("{}\n", LEASES)).unwrap();
+    assert_eq!(snapshot::load_cached(&source, &cache).unwrap(), leases);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
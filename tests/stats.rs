@@ -0,0 +1,165 @@
+extern crate dhcpd_parser;
+
+use std::time::Duration;
+
+use crate::dhcpd_parser::common::Date;
+use crate::dhcpd_parser::oui::OuiDb;
+use crate::dhcpd_parser::parser;
+use crate::dhcpd_parser::stats::{self, DayClients, Sample, StatsOptions};
+
+fn date(d: &str, t: &str) -> Date {
+    Date::from_timestamp(Date::from("0", d, t).unwrap().timestamp())
+}
+
+const JOURNAL: &str = "
+    lease 10.0.0.2 {
+        starts 2 2019/01/01 10:00:00;
+        ends 2 2019/01/01 11:00:00;
+        hardware ethernet 00:1a:2b:00:00:01;
+        client-hostname \"laptop\";
+    }
+    lease 10.0.0.2 {
+        starts 2 2019/01/01 11:00:00;
+        ends 2 2019/01/01 12:00:00;
+        hardware ethernet 00:1a:2b:00:00:01;
+        client-hostname \"laptop\";
+    }
+    lease 10.0.0.3 {
+        starts 2 2019/01/01 10:30:00;
+        ends 2 2019/01/01 10:40:00;
+        hardware ethernet 00:1A:2B:00:00:02;
+        hostname \"phone\";
+    }
+    lease 10.0.0.4 {
+        starts 4 2019/01/03 08:00:00;
+        ends 4 2019/01/03 09:00:00;
+        hardware ethernet 00:1a:2b:00:00:01;
+        client-hostname \"Laptop\";
+    }
+    lease 10.0.0.5 {
+        starts 4 2019/01/03 08:00:00;
+        ends 4 2019/01/03 09:00:00;
+        abandoned;
+    }
+";
+
+fn compute() -> stats::Stats {
+    let leases = parser::parse(JOURNAL).unwrap().leases;
+    stats::compute(
+        &leases,
+        &StatsOptions {
+            resolution: Duration::from_secs(1800),
+            top: 1,
+            now: date("2019/01/04", "00:00:00"),
+            oui: None,
+        },
+    )
+    .unwrap()
+}
+
+#[test]
+fn counts_test() {
+    let stats = compute();
+    assert_eq!(stats.declarations, 5);
+    assert_eq!(stats.clients, 2);
+    assert_eq!(stats.abandoned, 1);
+    assert_eq!(stats.renewals, 1);
+    assert_eq!(stats.renewal_rate, 0.25);
+    assert_eq!(stats.top_hostnames, vec![("laptop".to_owned(), 3)]);
+    assert_eq!(stats.top_ouis, vec![("00:1a:2b".to_owned(), 2)]);
+
+    let counts: Vec<usize> = stats.durations.iter().map(|b| b.count).collect();
+    assert_eq!(counts, vec![0, 1, 0, 4, 0, 0, 0, 0, 0]);
+    assert_eq!(stats.durations[3].min, Duration::from_secs(3600));
+    assert_eq!(stats.durations[8].max, None);
+}
+
+#[test]
+fn vendors_test() {
+    let leases = parser::parse(JOURNAL).unwrap().leases;
+    let mut oui = OuiDb::new();
+    oui.insert("00:1a:2b", "Example Corp").unwrap();
+
+    let stats = stats::compute(
+        &leases,
+        &StatsOptions {
+            oui: Some(oui),
+            ..StatsOptions::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(stats.top_vendors, vec![("Example Corp".to_owned(), 2)]);
+    assert!(compute().top_vendors.is_empty());
+}
+
+#[test]
+fn timeline_test() {
+    let stats = compute();
+    assert_eq!(
+        stats.peak,
+        Some(Sample {
+            at: date("2019/01/01", "10:30:00"),
+            active: 2,
+        })
+    );
+
+    let active: Vec<usize> = stats.timeline.iter().map(|s| s.active).collect();
+    assert_eq!(&active[..5], &[1, 2, 1, 1, 0]);
+    assert_eq!(stats.timeline[0].at, date("2019/01/01", "10:00:00"));
+    assert_eq!(
+        stats.timeline.last().unwrap().at,
+        date("2019/01/03", "09:00:00")
+    );
+}
+
+#[test]
+fn days_test() {
+    let stats = compute();
+    assert_eq!(
+        stats.days,
+        vec![
+            DayClients {
+                day: date("2019/01/01", "00:00:00"),
+                new: 2,
+                returning: 0,
+            },
+            DayClients {
+                day: date("2019/01/02", "00:00:00"),
+                new: 0,
+                returning: 0,
+            },
+            DayClients {
+                day: date("2019/01/03", "00:00:00"),
+                new: 0,
+                returning: 1,
+            },
+        ]
+    );
+}
+
+#[test]
+fn sample_limit_test() {
+    let mut journal = JOURNAL.to_owned();
+    journal.push_str(
+        "lease 10.0.0.6 {
+            starts 1 0001/01/01 00:00:00;
+            ends 1 0001/01/01 01:00:00;
+            hardware ethernet 00:1a:2b:00:00:03;
+        }",
+    );
+    let leases = parser::parse(journal).unwrap().leases;
+
+    let options = StatsOptions {
+        resolution: Duration::from_secs(1),
+        now: date("2019/01/04", "00:00:00"),
+        ..StatsOptions::default()
+    };
+    assert!(stats::compute(&leases, &options).is_err());
+
+    let options = StatsOptions {
+        resolution: Duration::from_secs(86400),
+        ..options
+    };
+    let stats = stats::compute(&leases, &options).unwrap();
+    assert_eq!(stats.timeline.len(), stats.days.len());
+}
@@ -0,0 +1,76 @@
+extern crate dhcpd_parser;
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+
+use crate::dhcpd_parser::store::{LeaseStore, ReloadEvent};
+
+const LEASES: &str = "
+    lease 10.0.0.2 {
+        starts 2 2019/01/01 10:00:00;
+        hardware ethernet 11:11:11:11:11:11;
+    }
+";
+
+fn temp_leases(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("dhcpd-parser-store-{}-{}", name, process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("dhcpd.leases");
+    fs::write(&path, LEASES).unwrap();
+
+    path
+}
+
+#[test]
+fn reload_keeps_last_good_snapshot_test() {
+    let path = temp_leases("reload");
+    let store = LeaseStore::open(&path).unwrap();
+    let events = store.subscribe();
+
+    let before = store.snapshot();
+    assert_eq!(before.len(), 1);
+
+    fs::write(&path, format!("{}\n lease 10.0.0.3 {{ starts 2 2019/13;", LEASES)).unwrap();
+    assert!(store.reload().is_err());
+    assert_eq!(store.snapshot(), before);
+    match events.try_recv().unwrap() {
+        ReloadEvent::Failed(_) => (),
+        e => panic!("Unexpected event {:?}", e),
+    }
+
+    fs::write(&path, format!("{}{}", LEASES, LEASES.replace("10.0.0.2", "10.0.0.3"))).unwrap();
+    let after = store.reload().unwrap();
+    assert_eq!(after.len(), 2);
+    assert_eq!(store.snapshot(), after);
+    assert_eq!(events.try_recv().unwrap(), ReloadEvent::Reloaded(after));
+
+    // snapshots handed out earlier are not affected
+    assert_eq!(before.len(), 1);
+
+    fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[cfg(feature = "watch")]
+#[test]
+fn watch_test() {
+    use std::time::Duration;
+
+    let path = temp_leases("watch");
+    let store = LeaseStore::watch(&path, Duration::from_millis(50)).unwrap();
+    let events = store.subscribe();
+
+    // dhcpd writes the new file next to the old one and renames it
+    let tmp = path.with_extension("new");
+    fs::write(&tmp, format!("{}{}", LEASES, LEASES.replace("10.0.0.2", "10.0.0.3"))).unwrap();
+    fs::rename(&tmp, &path).unwrap();
+
+    match events.recv_timeout(Duration::from_secs(10)).unwrap() {
+        ReloadEvent::Reloaded(leases) => assert_eq!(leases.len(), 2),
+        e => panic!("Unexpected event {:?}", e),
+    }
+    assert_eq!(store.snapshot().len(), 2);
+
+    fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
@@ -0,0 +1,65 @@
+extern crate dhcpd_parser;
+
+use crate::dhcpd_parser::parser;
+
+const JOURNAL: &str = "
+    lease 192.168.0.2 {
+        starts 2 2019/01/01 22:00:00 UTC;
+        ends 2 2019/01/01 23:00:00 UTC;
+        hardware type 11:11:11:11:11:11;
+    }
+
+    lease 192.168.0.3 {
+        starts 2 2019/01/01 22:10:00 UTC;
+        ends 2 2019/01/01 23:10:00 UTC;
+        hardware type 22:22:22:22:22:22;
+    }
+
+    lease 192.168.0.2 {
+        starts 2 2019/01/01 22:30:00 UTC;
+        ends 2 2019/01/01 23:30:00 UTC;
+        hardware type 11:11:11:11:11:11;
+    }
+
+    lease 192.168.0.2 {
+        starts 2 2019/01/01 23:00:00 UTC;
+        ends 3 2019/01/02 00:00:00 UTC;
+        hardware type 11:11:11:11:11:11;
+        hostname \"RENEWED\";
+    }
+";
+
+#[test]
+fn current_last_writer_wins_test() {
+    let leases = parser::parse(JOURNAL).unwrap().leases;
+    let table = leases.current();
+
+    assert_eq!(table.len(), 2);
+    assert_eq!(table.superseded_count(), 2);
+
+    let current = table.get("192.168.0.2").unwrap();
+    assert_eq!(current.hostname.as_ref().unwrap(), "RENEWED");
+    assert_eq!(
+        current.dates.ends.unwrap().to_string(),
+        "Wednesday 2019/01/02 00:00:00"
+    );
+    assert!(table.get("192.168.0.4").is_none());
+
+    // ordered by the position of the last declaration in the journal
+    assert_eq!(table.leases()[0].ip, "192.168.0.3");
+    assert_eq!(table.leases()[1].ip, "192.168.0.2");
+}
+
+#[test]
+fn current_history_test() {
+    let leases = parser::parse(JOURNAL).unwrap().leases;
+    let table = leases.current();
+
+    assert_eq!(table.superseded("192.168.0.2").len(), 2);
+    assert!(table.superseded("192.168.0.3").is_empty());
+
+    let history = table.history("192.168.0.2");
+    assert_eq!(history.len(), 3);
+    assert_eq!(history[0], leases[0]);
+    assert_eq!(history[2], leases[3]);
+}
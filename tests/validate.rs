@@ -0,0 +1,179 @@
+extern crate dhcpd_parser;
+
+use crate::dhcpd_parser::common::Date;
+use crate::dhcpd_parser::leases::{Leases, LeasesMethods};
+use crate::dhcpd_parser::parser::{self, ParserResult};
+use crate::dhcpd_parser::validate::{self, Code, Severity, ValidateOptions};
+
+fn findings(input: &str) -> Vec<(usize, Code)> {
+    let options = ValidateOptions {
+        now: Date::from("2", "2019/01/01", "12:00:00").unwrap(),
+        max_future_days: 30,
+    };
+
+    validate::validate_with(&parser::parse(input).unwrap(), &options)
+        .iter()
+        .map(|f| (f.lease, f.code))
+        .collect()
+}
+
+#[test]
+fn valid_test() {
+    let res = findings(
+        "
+        lease 10.0.0.2 {
+            starts 2 2019/01/01 10:00:00;
+            ends 2 2019/01/01 14:00:00;
+            hardware ethernet 00:1a:2b:3c:4d:5e;
+            uid 01:00:1a:2b:3c:4d:5e;
+        }
+
+        lease 10.0.0.2 {
+            starts 2 2019/01/01 14:00:00;
+            ends 3 2019/01/02 14:00:00;
+            hardware ethernet 0:1a:2b:3c:4d:5f;
+            uid \"client\";
+        }
+        ",
+    );
+    assert_eq!(res, []);
+}
+
+#[test]
+fn dates_test() {
+    let res = findings(
+        "
+        lease 10.0.0.2 {
+            starts 2 2019/01/01 10:00:00;
+            ends 1 2019/01/01 09:00:00;
+            hardware ethernet 00:1a:2b:3c:4d:5e;
+        }
+
+        lease 10.0.0.3 {
+            starts 2 2019/01/01 10:00:00;
+            ends 5 2019/03/01 10:00:00;
+            hardware ethernet 00:1a:2b:3c:4d:5f;
+        }
+        ",
+    );
+    assert_eq!(
+        res,
+        [
+            (0, Code::EndsBeforeStarts),
+            (0, Code::WeekdayMismatch),
+            (1, Code::FarFuture),
+        ]
+    );
+}
+
+#[test]
+fn identifiers_test() {
+    let res = findings(
+        "
+        lease 10.0.0.2 {
+            starts 2 2019/01/01 10:00:00;
+            hardware ethernet 00:1a:2b:3c:4d;
+            uid 01:xx;
+        }
+
+        lease 10.0.0.3 {
+            starts 2 2019/01/01 10:00:00;
+            hardware token-ring 00:1a:2b;
+            uid \"client;
+        }
+
+        lease 10.0.0.4 {
+            starts 2 2019/01/01 10:00:00;
+            abandoned;
+        }
+        ",
+    );
+    assert_eq!(
+        res,
+        [
+            (0, Code::MalformedMac),
+            (0, Code::MalformedUid),
+            (1, Code::MalformedUid),
+            (2, Code::AbandonedInWindow),
+            (2, Code::MissingHardware),
+        ]
+    );
+}
+
+#[test]
+fn overlapping_test() {
+    let input = "
+        lease 10.0.0.2 {
+            starts 2 2019/01/01 10:00:00;
+            ends 2 2019/01/01 14:00:00;
+            hardware ethernet 00:1a:2b:3c:4d:5e;
+        }
+
+        lease 10.0.0.2 {
+            starts 2 2019/01/01 12:00:00;
+            ends 2 2019/01/01 16:00:00;
+            hardware ethernet 00:1a:2b:3c:4d:5e;
+        }
+
+        lease 10.0.0.2 {
+            starts 2 2019/01/01 13:00:00;
+            ends 2 2019/01/01 15:00:00;
+            hardware ethernet 00:1a:2b:3c:4d:5f;
+        }
+
+        lease 10.0.0.2 {
+            starts 2 2019/01/01 16:00:00;
+            ends 2 2019/01/01 18:00:00;
+            hardware ethernet 00:1a:2b:3c:4d:60;
+        }
+    ";
+    assert_eq!(
+        findings(input),
+        [(2, Code::OverlappingLease), (2, Code::OverlappingLease)]
+    );
+
+    let options = ValidateOptions {
+        now: Date::from("2", "2019/01/01", "12:00:00").unwrap(),
+        max_future_days: 30,
+    };
+    let f = &validate::validate_with(&parser::parse(input).unwrap(), &options)[0];
+    assert_eq!(f.severity(), Severity::Error);
+    assert_eq!(
+        f.to_string(),
+        "L006 error 10.0.0.2: held by 00:1a:2b:3c:4d:5f while also held by 00:1a:2b:3c:4d:5e"
+    );
+}
+
+#[test]
+fn date_out_of_range_test() {
+    let parsed = parser::parse(
+        "
+        lease 10.0.0.2 {
+            starts 2 2019/01/01 10:00:00;
+            ends 2 2019/01/01 14:00:00;
+            hardware ethernet 00:1a:2b:3c:4d:5e;
+        }
+        lease 10.0.0.2 {
+            starts 2 2019/01/01 11:00:00;
+            hardware ethernet 00:1a:2b:3c:4d:5f;
+        }
+        ",
+    )
+    .unwrap()
+    .leases;
+    // not accepted by the parser, but the fields are public
+    let mut absurd = parsed[1].clone();
+    absurd.dates.ends = Some(Date {
+        year: 99999999999999,
+        ..Date::new()
+    });
+    let mut leases = Leases::new();
+    leases.push(parsed[0].clone());
+    leases.push(absurd);
+
+    let found = validate::validate(&ParserResult { leases });
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].code, Code::DateOutOfRange);
+    assert_eq!(found[0].lease, 1);
+    assert_eq!(found[0].severity(), Severity::Error);
+}